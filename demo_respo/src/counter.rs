@@ -5,12 +5,12 @@ use respo::{
   css::{respo_style, CssColor},
   div, span,
   ui::ui_button,
-  util, DispatchFn, RespoElement, RespoEvent,
+  util, DispatchFn, RespoElement, RespoError, RespoEvent,
 };
 use respo_state_derive::RespoState;
 use serde::{Deserialize, Serialize};
 
-use respo::states_tree::{RespoState, RespoStatesTree};
+use respo::states_tree::{RespoState, RespoStatesTree, StateCursor};
 
 use crate::IntentOp;
 
@@ -21,60 +21,40 @@ struct MainState {
   counted: i32,
 }
 
-pub fn comp_counter(states: &RespoStatesTree, global_counted: i32) -> Result<RespoElement<ActionOp>, String> {
-  let cursor = states.path();
-
-  let state = states.cast_branch::<MainState>();
-  let counted = state.counted;
+pub fn comp_counter(states: &RespoStatesTree, global_counted: i32) -> Result<RespoElement<ActionOp>, RespoError> {
+  let cursor = StateCursor::<MainState>::new(states)?;
+  let counted = cursor.counted;
 
   let on_inc = {
     let cursor = cursor.to_owned();
-    let state = state.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
       if let RespoEvent::Click { original_event, .. } = e {
         original_event.prevent_default();
       }
 
       dispatch.run(ActionOp::Increment)?;
-      dispatch.run_state(
-        &cursor,
-        MainState {
-          counted: state.counted + 2,
-        },
-      )?;
+      dispatch.run(cursor.update(|s| s.counted += 2))?;
       Ok(())
     }
   };
 
   let on_dec = {
     let cursor = cursor.to_owned();
-    let state = state.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
       dispatch.run(ActionOp::Decrement)?;
-      dispatch.run_state(
-        &cursor,
-        MainState {
-          counted: state.counted - 1,
-        },
-      )?;
+      dispatch.run(cursor.update(|s| s.counted -= 1))?;
       Ok(())
     }
   };
 
   let on_inc_twice = {
     let cursor = cursor.to_owned();
-    let state = state.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
       dispatch.run(ActionOp::Intent(IntentOp::IncTwice))?;
-      dispatch.run_state(
-        &cursor,
-        MainState {
-          counted: state.counted + 2,
-        },
-      )?;
+      dispatch.run(cursor.update(|s| s.counted += 2))?;
       Ok(())
     }
   };
@@ -2,7 +2,7 @@
 
 use std::fmt::Debug;
 
-use respo::{button, css::respo_style, div, span, ui::ui_button, util, DispatchFn, RespoElement, RespoEvent};
+use respo::{button, css::respo_style, div, span, ui::ui_button, util, DispatchFn, RespoElement, RespoError, RespoEvent};
 use respo_state_derive::RespoState;
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +15,7 @@ struct InnerTextState {
   inner_text: bool,
 }
 
-pub fn comp_inner_text(states: &RespoStatesTree) -> Result<RespoElement<ActionOp>, String> {
+pub fn comp_inner_text(states: &RespoStatesTree) -> Result<RespoElement<ActionOp>, RespoError> {
   let cursor = states.path();
 
   let state = states.cast_branch::<InnerTextState>();
@@ -23,7 +23,7 @@ pub fn comp_inner_text(states: &RespoStatesTree) -> Result<RespoElement<ActionOp
   let on_inc = {
     let cursor = cursor.to_owned();
     let state = state.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
       if let RespoEvent::Click { original_event, .. } = e {
         original_event.prevent_default();
@@ -14,7 +14,7 @@ use std::rc::Rc;
 
 use inner_text::comp_inner_text;
 use respo::css::respo_style;
-use respo::{contained_styles, space, RespoAction};
+use respo::{contained_styles, space, RespoAction, RespoError};
 use web_sys::Node;
 
 use respo::ui::ui_global;
@@ -49,7 +49,11 @@ impl RespoApp for App {
     APP_STORE_KEY
   }
 
-  fn dispatch(store_to_action: Rc<RefCell<Self::Model>>, op: <Self::Model as RespoStore>::Action) -> Result<(), String> {
+  fn prune_states() -> bool {
+    true
+  }
+
+  fn dispatch(store_to_action: Rc<RefCell<Self::Model>>, op: <Self::Model as RespoStore>::Action) -> Result<(), RespoError> {
     if let Some(intent) = op.detect_intent() {
       intent.update(store_to_action)
     } else {
@@ -58,7 +62,7 @@ impl RespoApp for App {
     }
   }
 
-  fn view(store: Ref<Self::Model>) -> Result<RespoNode<<Self::Model as RespoStore>::Action>, String> {
+  fn view(store: Ref<Self::Model>) -> Result<RespoNode<<Self::Model as RespoStore>::Action>, RespoError> {
     let states = &store.states;
     // util::log!("global store: {:?}", store);
 
@@ -90,8 +94,12 @@ fn main() {
     store: Rc::new(RefCell::new(Store::default())),
   };
 
-  app.try_load_storage().expect("load storage");
+  if let Err(e) = app.try_load_storage() {
+    // broken content is moved aside, rendering starts from the default store
+    util::warn_log!("failed to load storage: {}", e);
+  }
   app.backup_model_beforeunload().expect("backup model beforeunload");
+  app.backup_model_on_hidden().expect("backup model on hidden");
 
   util::log!("store: {:?}", app.store);
 
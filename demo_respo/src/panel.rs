@@ -9,7 +9,7 @@ use crate::store::ActionOp;
 use respo::{
   button, div, input, space, span,
   ui::{ui_button, ui_input},
-  util, DispatchFn, RespoComponent, RespoEffect, RespoEvent, RespoNode,
+  util, DispatchFn, RespoComponent, RespoEffect, RespoEffectNode, RespoError, RespoEvent, RespoNode,
 };
 
 use respo::states_tree::{RespoState, RespoStatesTree};
@@ -23,18 +23,18 @@ struct PanelState {
 struct PanelMount {}
 
 impl RespoEffect for PanelMount {
-  fn updated(&self, _el: &web_sys::Node) -> Result<(), String> {
+  fn updated(&self, _el: RespoEffectNode) -> Result<(), RespoError> {
     respo::util::log!("panel updated");
     Ok(())
   }
 
-  fn mounted(&self, _el: &web_sys::Node) -> Result<(), String> {
+  fn mounted(&self, _el: RespoEffectNode) -> Result<(), RespoError> {
     respo::util::log!("panel mounted");
     Ok(())
   }
 }
 
-pub fn comp_panel(states: &RespoStatesTree) -> Result<RespoNode<ActionOp>, String> {
+pub fn comp_panel(states: &RespoStatesTree) -> Result<RespoNode<ActionOp>, RespoError> {
   let cursor = states.path();
   let state = states.cast_branch::<PanelState>();
 
@@ -52,7 +52,7 @@ pub fn comp_panel(states: &RespoStatesTree) -> Result<RespoNode<ActionOp>, Strin
   let on_submit = {
     let state = state.to_owned();
     let cursor = cursor.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("add button {:?}", e);
       dispatch.run(ActionOp::AddTask(Uuid::new_v4().to_string(), state.content.to_owned()))?;
       dispatch.run_state(&cursor, PanelState { content: "".to_owned() })?;
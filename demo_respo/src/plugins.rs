@@ -1,6 +1,6 @@
 use respo::css::respo_style;
 use respo::ui::{ui_button_danger, ui_button_primary};
-use respo::{space, ui::ui_row_parted};
+use respo::{space, ui::ui_row_parted, RespoError};
 use respo::{RespoElement, RespoEvent};
 
 use respo::{button, div, span, ui::ui_button, util, DispatchFn};
@@ -15,7 +15,7 @@ use respo::ui::dialog::{
 
 use super::store::*;
 
-pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<ActionOp>, String> {
+pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<ActionOp>, RespoError> {
   // respo::util::log!("re-render");
 
   let alert_plugin = AlertPlugin::new(
@@ -33,7 +33,7 @@ pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<Action
 
   let on_alert = {
     let alert_plugin = alert_plugin.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       // alert_plugin.show(dispatch, Some("a mesasge for you".to_owned()))?;
@@ -55,7 +55,7 @@ pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<Action
 
   let on_confirm = {
     let confirm_plugin = confirm_plugin.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       confirm_plugin.show(dispatch, move || {
@@ -92,7 +92,7 @@ pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<Action
 
   let on_prompt = {
     let prompt_plugin = prompt_plugin.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       prompt_plugin.show(dispatch, move |content| {
@@ -134,7 +134,7 @@ pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<Action
 
   let on_modal = {
     let modal_plugin = modal_plugin.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       modal_plugin.show(dispatch)?;
@@ -173,7 +173,7 @@ pub fn comp_plugins_demo(states: &RespoStatesTree) -> Result<RespoElement<Action
 
   let on_drawer = {
     let drawer_plugin = drawer_plugin.to_owned();
-    move |e: RespoEvent, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e: RespoEvent, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       drawer_plugin.show(dispatch)?;
@@ -1,6 +1,6 @@
 use std::{cell::RefCell, hash::Hash, rc::Rc};
 
-use respo::{states_tree::RespoUpdateState, util, RespoAction, RespoStore};
+use respo::{states_tree::RespoUpdateState, util, RespoAction, RespoError, RespoStore};
 use respo_state_derive::RespoState;
 use serde::{Deserialize, Serialize};
 
@@ -39,7 +39,7 @@ pub enum IntentOp {
 }
 
 impl IntentOp {
-  pub fn update(&self, store_to_action: Rc<RefCell<Store>>) -> Result<(), String> {
+  pub fn update(&self, store_to_action: Rc<RefCell<Store>>) -> Result<(), RespoError> {
     use IntentOp::*;
     let mut store = store_to_action.borrow_mut();
     match self {
@@ -101,7 +101,7 @@ impl RespoStore for Store {
     &mut self.states
   }
 
-  fn update(&mut self, op: Self::Action) -> Result<(), String> {
+  fn update(&mut self, op: Self::Action) -> Result<(), RespoError> {
     use ActionOp::*;
     match op {
       Noop => {} // nothing to to
@@ -133,7 +133,7 @@ impl RespoStore for Store {
           }
         }
         if !found {
-          return Err(format!("task {} not found", id));
+          return Err(format!("task {} not found", id).into());
         }
       }
       ToggleTask(id) => {
@@ -146,7 +146,7 @@ impl RespoStore for Store {
           }
         }
         if !found {
-          return Err(format!("task {} not found", id));
+          return Err(format!("task {} not found", id).into());
         }
       }
     }
@@ -157,10 +157,10 @@ impl RespoStore for Store {
     serde_json::to_string(&self).expect("to json")
   }
 
-  fn try_from_string(s: &str) -> Result<Self, String>
+  fn try_from_string(s: &str) -> Result<Self, RespoError>
   where
     Self: Sized,
   {
-    serde_json::from_str(s).map_err(|e| format!("parse store: {}", e))
+    serde_json::from_str(s).map_err(|e| RespoError::StateRestore(format!("parse store: {}", e)))
   }
 }
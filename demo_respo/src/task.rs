@@ -11,7 +11,7 @@ use respo::{
   },
   div, input, space, span, static_styles,
   ui::{ui_button, ui_center, ui_input, ui_row_middle},
-  util, DispatchFn, RespoComponent, RespoEffect, RespoEvent, RespoNode,
+  util, DispatchFn, RespoComponent, RespoEffect, RespoEffectNode, RespoError, RespoEvent, RespoNode,
 };
 
 use respo::states_tree::{RespoState, RespoStatesTree};
@@ -29,7 +29,7 @@ struct TaskUpdateEffect {
 }
 
 impl RespoEffect for TaskUpdateEffect {
-  fn updated(&self, _el: &web_sys::Node) -> Result<(), String> {
+  fn updated(&self, _el: RespoEffectNode) -> Result<(), RespoError> {
     util::log!("task update effect");
     Ok(())
   }
@@ -40,7 +40,7 @@ pub fn comp_task(
   // _memo_caches: MemoCache<RespoNode<ActionOp>>,
   states: RespoStatesTree,
   task: Task,
-) -> Result<RespoNode<ActionOp>, String> {
+) -> Result<RespoNode<ActionOp>, RespoError> {
   respo::util::log!("calling task function");
 
   let task_id = &task.id;
@@ -50,7 +50,7 @@ pub fn comp_task(
 
   let on_toggle = {
     let tid = task_id.to_owned();
-    move |_e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |_e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       dispatch.run(ActionOp::ToggleTask(tid.to_owned()))?;
       Ok(())
     }
@@ -58,7 +58,7 @@ pub fn comp_task(
 
   let on_input = {
     let cursor = cursor.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       if let RespoEvent::Input { value, .. } = e {
         dispatch.run_state(&cursor, TaskState { draft: value })?;
       }
@@ -68,7 +68,7 @@ pub fn comp_task(
 
   let on_remove = {
     let tid = task_id.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("remove button {:?}", e);
       dispatch.run(ActionOp::RemoveTask(tid.to_owned()))?;
       Ok(())
@@ -79,7 +79,7 @@ pub fn comp_task(
     let tid = task_id.to_owned();
     let cursor = cursor.to_owned();
     let state = state.to_owned();
-    move |_e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |_e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       dispatch.run(ActionOp::UpdateTask(tid.to_owned(), state.draft.to_owned()))?;
       dispatch.run_empty_state(&cursor)?;
       Ok(())
@@ -1,3 +1,4 @@
+use respo::RespoError;
 use respo::{button, div, span, ui::ui_button, util, DispatchFn, RespoElement, RespoIndexKey, RespoNode};
 use respo_state_derive::RespoState;
 use serde::{Deserialize, Serialize};
@@ -14,7 +15,7 @@ struct TodolistState {
   hide_done: bool,
 }
 
-pub fn comp_todolist(states: &RespoStatesTree, tasks: &[Task]) -> Result<RespoElement<ActionOp>, String> {
+pub fn comp_todolist(states: &RespoStatesTree, tasks: &[Task]) -> Result<RespoElement<ActionOp>, RespoError> {
   let cursor = states.path();
   let state = states.cast_branch::<TodolistState>();
 
@@ -31,7 +32,7 @@ pub fn comp_todolist(states: &RespoStatesTree, tasks: &[Task]) -> Result<RespoEl
 
   let on_hide = {
     let state = state.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       util::log!("click {:?}", e);
 
       dispatch.run_state(
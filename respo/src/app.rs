@@ -1,6 +1,15 @@
+pub(crate) mod backend;
 pub(crate) mod diff;
+pub(crate) mod memo;
 pub(crate) mod patch;
 pub(crate) mod renderer;
+pub(crate) mod scheduler;
+pub(crate) mod storage;
+pub(crate) mod time_travel;
+pub(crate) mod trace;
+
+#[cfg(test)]
+mod tests;
 
 use std::{
   cell::{Ref, RefCell},
@@ -11,10 +20,20 @@ use std::{
 pub mod util;
 
 use wasm_bindgen::{closure::Closure, JsCast};
-use web_sys::{BeforeUnloadEvent, Node};
+use web_sys::{BeforeUnloadEvent, Event, Node, VisibilityState};
+
+use renderer::{render_node, SweepStatesFn};
+use scheduler::RenderScheduler;
+use storage::{backup_store, load_store, BackupScheduler};
+use trace::FrameRecorder;
 
-use renderer::render_node;
+pub use backend::{DomBackend, MemoryBackend, MemoryNode, RespoEventCallback, WebBackend};
+pub use renderer::RespoRenderer;
+pub use storage::{IndexedDbStorage, LocalStorage, MemoryStorage, RespoStorage, SessionStorage, StorageLoadFn, StorageMigration};
+pub use time_travel::{RespoTimeTravel, StoreDispatchFn};
+pub use trace::{ConsoleTracer, RespoFrameTrace, RespoTracer, RingBufferTracer};
 
+use crate::RespoError;
 use crate::{
   node::{DispatchFn, RespoAction, RespoNode},
   states_tree::{RespoStatesTree, RespoUpdateState},
@@ -28,81 +47,110 @@ pub trait RespoApp {
   type Model: RespoStore + Debug + Clone + 'static;
 
   /// simulating pure function updates to the model, but actually it's mutations
-  fn dispatch(store: Rc<RefCell<Self::Model>>, action: <Self::Model as RespoStore>::Action) -> Result<(), String>;
+  fn dispatch(store: Rc<RefCell<Self::Model>>, action: <Self::Model as RespoStore>::Action) -> Result<(), RespoError>;
 
   /// used when saving to local storage
   fn pick_storage_key() -> &'static str {
     RESPO_APP_STORE_KEY
   }
 
+  /// migrations for stores saved by older versions of the app, the one at index `n` upgrades content
+  /// from version `n` to `n + 1`, and the app is of version `n + 1` after adding it.
+  /// Content saved before versions were tracked is of version `0`.
+  ///
+  /// ```rust
+  /// use respo::{RespoError, StorageMigration};
+  ///
+  /// /// version 1 renamed field `count` to `counted`
+  /// fn rename_count(s: String) -> Result<String, RespoError> {
+  ///   Ok(s.replace("\"count\":", "\"counted\":"))
+  /// }
+  ///
+  /// let migrations: Vec<StorageMigration> = vec![rename_count];
+  /// # assert_eq!(migrations[0]("{\"count\":1}".to_owned()), Ok("{\"counted\":1}".to_owned()));
+  /// ```
+  fn storage_migrations() -> Vec<StorageMigration> {
+    vec![]
+  }
+
   /// bridge to mount target
   fn get_mount_target(&self) -> &Node;
   /// bridge to store
   fn get_store(&self) -> &Rc<RefCell<Self::Model>>;
 
-  /// default interval in milliseconds, by default 100ms,
-  /// pass `None` to use raq directly, pass `Some(200)` to redice cost
+  /// where the store is saved into and loaded from, by default local storage
+  fn get_storage(&self) -> Rc<dyn RespoStorage> {
+    Rc::new(LocalStorage)
+  }
+
+  /// milliseconds to wait after the last dispatching before saving store into storage,
+  /// by default `None`, saving happens only with `backup_model_beforeunload` or `backup_model_on_hidden`
+  fn get_backup_delay() -> Option<i32> {
+    None
+  }
+
+  /// minimal interval in milliseconds between renders, by default 100ms.
+  /// Renders are requested only when actions are dispatched, pass `None` to render in next frame,
+  /// pass `Some(200)` to reduce cost
   fn get_loop_delay() -> Option<i32> {
     Some(100)
   }
 
-  /// DSL for building a view
-  fn view(store: Ref<Self::Model>) -> Result<RespoNode<<Self::Model as RespoStore>::Action>, String>;
-  /// start a requestAnimationFrame loop for rendering updated store
-  fn render_loop(&self) -> Result<(), String> {
-    let mount_target = self.get_mount_target();
-    let global_store = self.get_store();
-
-    // let store_to_action = global_store.to_owned();
-    let dispatch_action = {
-      let store_to_action = global_store.to_owned();
-      move |op: <Self::Model as RespoStore>::Action| -> Result<(), String> {
-        // util::log!("action {:?} store, {:?}", op, store_to_action.borrow());
-
-        Self::dispatch(store_to_action.to_owned(), op)?;
-        // util::log!("store after action {:?}", store);
-        Ok(())
-      }
-    };
+  /// receives a trace of each frame rendered after dispatching, with actions, changes and times spent.
+  /// Not traced by default, return a `ConsoleTracer` or a `RingBufferTracer` to enable it
+  fn tracer(&self) -> Option<Rc<dyn RespoTracer<<Self::Model as RespoStore>::Action>>> {
+    None
+  }
 
-    render_node(
-      mount_target.to_owned(),
-      Box::new({
-        let store_to_action = global_store.to_owned();
-        move || store_to_action.borrow().to_owned()
-      }),
-      Box::new({
-        let store = global_store.to_owned();
-        move || -> Result<RespoNode<<Self::Model as RespoStore>::Action>, String> {
-          // util::log!("global store: {:?}", store);
+  /// records dispatched actions for stepping back and forth, created with the store and `dispatch` of the app,
+  /// like `RespoTimeTravel::new(&store, App::dispatch)`. Keep a clone to jump in history, the app renders after each jump.
+  /// States are not pruned while recording, see `prune_states`. Not recorded by default
+  fn time_travel(&self) -> Option<RespoTimeTravel<Self::Model>> {
+    None
+  }
 
-          Self::view(store.borrow())
-        }
-      }),
-      DispatchFn::new(dispatch_action),
-      Self::get_loop_delay(),
-    )
-    .unwrap_or_else(|e| {
-      util::error_log!("render loop error: {:?}", e);
-    });
+  /// drops branches of states tree that are not picked in a render, so states of removed components
+  /// are not kept forever or saved into storage. Branches picked with `pick_persistent` are kept,
+  /// and nothing is dropped in renders where a boundary caught an error, or when `time_travel` is enabled.
+  /// By default `false`, notice that states of components hidden for a while are dropped as well
+  fn prune_states() -> bool {
+    false
+  }
 
-    Ok(())
+  /// DSL for building a view
+  fn view(store: Ref<Self::Model>) -> Result<RespoNode<<Self::Model as RespoStore>::Action>, RespoError>;
+  /// renders into mount target, and renders again in a `requestAnimationFrame` after actions are dispatched.
+  /// returns a handle for unmounting the app
+  fn render_loop(&self) -> Result<RespoAppHandle, RespoError> {
+    let (handle, _) = start_render_node(self, false)?;
+    Ok(handle)
   }
 
-  /// backup store to local storage before unload
-  fn backup_model_beforeunload(&self) -> Result<(), String> {
+  /// like `render_loop`, but reuses DOM under mount target that was rendered with `render_to_html` on server,
+  /// event listeners are attached and `Mounted` effects are called instead of creating the DOM again.
+  /// returns mismatches between the DOM and the first virtual tree, mismatched parts are patched to match virtual tree.
+  fn hydrate_loop(&self) -> Result<(RespoAppHandle, Vec<String>), RespoError> {
+    let (handle, mismatches) = start_render_node(self, true)?;
+    for m in &mismatches {
+      util::warn_log!("hydration mismatch: {}", m);
+    }
+    Ok((handle, mismatches))
+  }
+
+  /// backup store to storage before unload, along with version of the app, see `storage_migrations`.
+  /// `beforeunload` is not fired reliably on mobile, use it along with `backup_model_on_hidden`
+  fn backup_model_beforeunload(&self) -> Result<(), RespoError> {
     let window = web_sys::window().expect("window");
-    let storage = match window.local_storage() {
-      Ok(Some(storage)) => storage,
-      _ => return Err("Failed to access local storage".to_owned()),
-    };
     let beforeunload = Closure::wrap(Box::new({
       let p = Self::pick_storage_key();
+      let version = Self::storage_migrations().len();
+      let storage = self.get_storage();
       let store = self.get_store().to_owned();
       move |_e: BeforeUnloadEvent| {
-        let content = store.as_ref().borrow().to_string();
-        // util::log!("before unload {} {}", p, content);
-        storage.set_item(p, &content).expect("save storage");
+        // util::log!("before unload {} {}", p, store.borrow().to_string());
+        if let Err(e) = backup_store(storage.as_ref(), p, version, &store) {
+          util::error_log!("failed to backup store: {}", e);
+        }
       }
     }) as Box<dyn FnMut(BeforeUnloadEvent)>);
     window.set_onbeforeunload(Some(beforeunload.as_ref().unchecked_ref()));
@@ -110,36 +158,215 @@ pub trait RespoApp {
     Ok(())
   }
 
-  fn try_load_storage(&self) -> Result<(), String> {
+  /// backup store to storage when the page is hidden, like switching tabs or apps on mobile,
+  /// which happens more reliably than `beforeunload`
+  fn backup_model_on_hidden(&self) -> Result<(), RespoError> {
     let window = web_sys::window().expect("window");
-    let storage = match window.local_storage() {
-      Ok(Some(storage)) => storage,
-      _ => return Err("Failed to access local storage".to_owned()),
-    };
-
-    let key = Self::pick_storage_key();
-    match storage.get_item(key) {
-      Ok(Some(s)) => match Self::Model::try_from_string(&s) {
-        Ok(s) => {
-          let store = self.get_store();
-          *store.borrow_mut() = s;
+    let document = window
+      .document()
+      .ok_or_else(|| RespoError::Dom("Failed to access document".to_owned()))?;
+    let on_change = Closure::wrap(Box::new({
+      let document = document.to_owned();
+      let p = Self::pick_storage_key();
+      let version = Self::storage_migrations().len();
+      let storage = self.get_storage();
+      let store = self.get_store().to_owned();
+      move |_e: Event| {
+        if document.visibility_state() != VisibilityState::Hidden {
+          return;
         }
-        Err(e) => {
-          util::error_log!("error: {:?}", e);
+        if let Err(e) = backup_store(storage.as_ref(), p, version, &store) {
+          util::error_log!("failed to backup store: {}", e);
         }
-      },
-      _ => {
-        util::log!("no storage");
       }
-    }
+    }) as Box<dyn FnMut(Event)>);
+    document.add_event_listener_with_callback("visibilitychange", on_change.as_ref().unchecked_ref())?;
+    on_change.forget();
     Ok(())
   }
+
+  /// loads store from storage, see `load_storage`. With asynchronous storages like IndexedDB
+  /// it returns before loading finishes, use `load_storage` to start rendering after that
+  fn try_load_storage(&self) -> Result<(), RespoError> {
+    let result = Rc::new(RefCell::new(Ok(())));
+    self.load_storage({
+      let result = result.to_owned();
+      move |r| {
+        *result.borrow_mut() = r;
+      }
+    });
+    result.replace(Ok(()))
+  }
+
+  /// loads store from storage, older content is upgraded with `storage_migrations`, then `done` is called.
+  /// Content that fails to load is moved to key `{key}:failed` for recovering by hand, since it's overwritten in next backup,
+  /// and `done` receives the error of loading
+  fn load_storage<F>(&self, done: F)
+  where
+    F: FnOnce(Result<(), RespoError>) + 'static,
+  {
+    load_store(
+      self.get_storage(),
+      Self::pick_storage_key(),
+      Self::storage_migrations(),
+      self.get_store().to_owned(),
+      done,
+    )
+  }
+}
+
+/// shared by `render_loop` and `hydrate_loop`, wires store, view and dispatch into the renderer
+fn start_render_node<A>(app: &A, hydrate: bool) -> Result<(RespoAppHandle, Vec<String>), RespoError>
+where
+  A: RespoApp + ?Sized,
+{
+  let mount_target = app.get_mount_target();
+  let global_store = app.get_store();
+  let scheduler = RenderScheduler::new(A::get_loop_delay());
+  let recorder = app.tracer().map(FrameRecorder::new);
+  let time_travel = app.time_travel();
+  if let Some(travel) = &time_travel {
+    let scheduler = scheduler.to_owned();
+    travel.attach(Box::new(move || scheduler.schedule()));
+  }
+
+  // sweeping is not a recorded action, replaying history would not reproduce it
+  let sweep_states: Option<SweepStatesFn> = if A::prune_states() && time_travel.is_none() {
+    let store = global_store.to_owned();
+    Some(Box::new(move |picks| {
+      store.borrow_mut().get_states().sweep(picks);
+    }))
+  } else {
+    None
+  };
+
+  let backup = A::get_backup_delay().map(|delay| {
+    let key = A::pick_storage_key();
+    let version = A::storage_migrations().len();
+    let storage = app.get_storage();
+    let store = global_store.to_owned();
+    BackupScheduler::new(
+      delay,
+      Box::new(move || {
+        if let Err(e) = backup_store(storage.as_ref(), key, version, &store) {
+          util::error_log!("failed to backup store: {}", e);
+        }
+      }),
+    )
+  });
+
+  let dispatch_action = {
+    let store_to_action = global_store.to_owned();
+    let scheduler = scheduler.to_owned();
+    let recorder = recorder.to_owned();
+    move |op: <A::Model as RespoStore>::Action| -> Result<(), RespoError> {
+      // util::log!("action {:?} store, {:?}", op, store_to_action.borrow());
+      if let Some(recorder) = &recorder {
+        recorder.record_action(&op);
+      }
+
+      let changed = dispatch_changes_store(&store_to_action, || match &time_travel {
+        Some(travel) => travel.dispatch(op),
+        None => A::dispatch(store_to_action.to_owned(), op),
+      })?;
+      // util::log!("store after action {:?}", store);
+      if changed {
+        scheduler.schedule();
+        if let Some(backup) = &backup {
+          backup.schedule();
+        }
+      }
+      Ok(())
+    }
+  };
+
+  render_node(
+    mount_target.to_owned(),
+    Box::new({
+      let store = global_store.to_owned();
+      move || -> Result<RespoNode<<A::Model as RespoStore>::Action>, RespoError> {
+        // util::log!("global store: {:?}", store);
+
+        A::view(store.borrow())
+      }
+    }),
+    DispatchFn::new(dispatch_action),
+    scheduler,
+    recorder,
+    sweep_states,
+    hydrate,
+  )
+}
+
+/// runs `dispatch` on the store, returns whether the store might be changed by comparing `RespoStore::version`,
+/// along with revision of the states tree, since states are updated without the store counting them
+pub(crate) fn dispatch_changes_store<S>(
+  store: &Rc<RefCell<S>>,
+  dispatch: impl FnOnce() -> Result<(), RespoError>,
+) -> Result<bool, RespoError>
+where
+  S: RespoStore,
+{
+  let prev_version = store_version(&mut *store.borrow_mut());
+  dispatch()?;
+  let version = store_version(&mut *store.borrow_mut());
+  Ok(version.is_none() || version != prev_version)
+}
+
+fn store_version<S>(store: &mut S) -> Option<(u64, u64)>
+where
+  S: RespoStore,
+{
+  let version = store.version()?;
+  Some((version, store.get_states().revision()))
+}
+
+/// stops an app, see `RespoAppHandle::unmount`
+pub(crate) type TeardownFn = Box<dyn FnOnce() -> Result<(), RespoError>>;
+
+/// handle of an app started with `render_loop` or `hydrate_loop`.
+///
+/// The app keeps running when the handle is dropped, call `unmount` to stop it.
+pub struct RespoAppHandle {
+  scheduler: RenderScheduler,
+  teardown: RefCell<Option<TeardownFn>>,
+}
+
+impl Debug for RespoAppHandle {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RespoAppHandle").field("scheduler", &self.scheduler).finish()
+  }
+}
+
+impl RespoAppHandle {
+  pub(crate) fn new(scheduler: RenderScheduler, teardown: TeardownFn) -> Self {
+    Self {
+      scheduler,
+      teardown: RefCell::new(Some(teardown)),
+    }
+  }
+
+  /// stops rendering, runs `BeforeUnmount` effects inside-out, detaches event listeners,
+  /// then removes rendered elements and static styles of the app. Later calls do nothing
+  pub fn unmount(&self) -> Result<(), RespoError> {
+    self.scheduler.stop();
+    let teardown = self.teardown.borrow_mut().take();
+    match teardown {
+      Some(f) => f(),
+      None => Ok(()),
+    }
+  }
+
+  /// whether `unmount` has not been called
+  pub fn is_mounted(&self) -> bool {
+    !self.scheduler.is_stopped()
+  }
 }
 
 /// it has a states tree inside, and it does update itself
 pub trait RespoStore {
   type Action: Debug + Clone + RespoAction;
-  fn update(&mut self, op: Self::Action) -> Result<(), String>;
+  fn update(&mut self, op: Self::Action) -> Result<(), RespoError>;
 
   /// a way to load states tree
   fn get_states(&mut self) -> &mut RespoStatesTree;
@@ -149,11 +376,20 @@ pub trait RespoStore {
     self.get_states().set_in_mut(op);
   }
 
+  /// opt-in version of the store, it should be cheap and change whenever the store is updated,
+  /// for example a counter increased in `update`. Updates with `update_states` are tracked by `RespoStatesTree::revision`,
+  /// they don't need to be counted here.
+  /// When it's `Some(..)` and unchanged after dispatching an action, view is not called and nothing is rendered.
+  /// By default `None`, every dispatching causes a render.
+  fn version(&self) -> Option<u64> {
+    None
+  }
+
   /// for backup
   fn to_string(&self) -> String;
 
   /// load from backup
-  fn try_from_string(s: &str) -> Result<Self, String>
+  fn try_from_string(s: &str) -> Result<Self, RespoError>
   where
     Self: Sized;
 }
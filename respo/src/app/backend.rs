@@ -0,0 +1,103 @@
+//! DOM operations used by the renderer, abstracted so diffing and patching are not bound to the browser.
+//!
+//! - `WebBackend` operates on `web_sys` nodes, it's used by `RespoApp::render_loop`.
+//! - `MemoryBackend` keeps a tree of nodes in memory, for running the renderer natively, like in `cargo test`.
+
+mod memory;
+mod web;
+
+use std::{fmt::Debug, rc::Rc};
+
+use crate::node::{RespoEffect, RespoEffectType, RespoEvent, RespoEventOptions};
+use crate::RespoError;
+
+pub use memory::{MemoryBackend, MemoryNode};
+pub use web::WebBackend;
+
+/// callback for events from the DOM, location of the element is already captured inside
+pub type RespoEventCallback = Rc<dyn Fn(RespoEvent) -> Result<(), RespoError>>;
+
+/// operations that the renderer performs on a DOM.
+///
+/// Besides plain attributes, `set_attribute` and `remove_attribute` also receive properties
+/// `innerText`, `innerHTML`, `htmlFor` and `value`, which backends handle like browsers do.
+pub trait DomBackend {
+  /// handle to a node, cloning it does not clone the node
+  type Node: Clone + Debug;
+
+  /// creates an element with tag name
+  fn create_element(&self, name: &str) -> Result<Self::Node, RespoError>;
+  fn set_attribute(&self, el: &Self::Node, key: &str, value: &str) -> Result<(), RespoError>;
+  fn remove_attribute(&self, el: &Self::Node, key: &str) -> Result<(), RespoError>;
+  /// sets a single inline style property
+  fn set_style(&self, el: &Self::Node, property: &str, value: &str) -> Result<(), RespoError>;
+  fn remove_style(&self, el: &Self::Node, property: &str) -> Result<(), RespoError>;
+
+  /// tag name in lowercase, `None` for text nodes and comments
+  fn tag_name(&self, node: &Self::Node) -> Option<String>;
+  /// reads what `set_attribute` sets, properties included, `None` when it's not set.
+  /// `innerText` is read as text content, which leaves out line breaks written as `<br>`. `innerText` of a text node is its content
+  fn get_attribute(&self, node: &Self::Node, key: &str) -> Option<String>;
+  /// names of attributes as the DOM reports them, properties like `innerText` are not included
+  fn attribute_names(&self, el: &Self::Node) -> Vec<String>;
+  /// inline style properties with values
+  fn styles(&self, el: &Self::Node) -> Vec<(String, String)>;
+
+  /// get child node at index, text nodes are counted
+  fn child_at(&self, parent: &Self::Node, idx: u32) -> Option<Self::Node>;
+  fn child_count(&self, parent: &Self::Node) -> u32;
+  fn parent_of(&self, node: &Self::Node) -> Option<Self::Node>;
+  fn append_child(&self, parent: &Self::Node, child: &Self::Node) -> Result<(), RespoError>;
+  /// inserts `child` before `reference`, which is a child of `parent`
+  fn insert_before(&self, parent: &Self::Node, child: &Self::Node, reference: &Self::Node) -> Result<(), RespoError>;
+  fn remove_child(&self, parent: &Self::Node, child: &Self::Node) -> Result<(), RespoError>;
+
+  /// listens to event of `name` on element, replacing the previous listener of `name`.
+  /// `options` are applied to the DOM event before `callback` is called
+  fn attach_listener(
+    &self,
+    el: &Self::Node,
+    name: &str,
+    options: RespoEventOptions,
+    callback: RespoEventCallback,
+  ) -> Result<(), RespoError>;
+  /// stops listening to event of `name` on element
+  fn detach_listener(&self, el: &Self::Node, name: &str) -> Result<(), RespoError>;
+
+  /// runs a lifecycle hook of an effect, with the element of the component
+  fn run_effect(&self, effect: &dyn RespoEffect, effect_type: RespoEffectType, el: &Self::Node) -> Result<(), RespoError>;
+}
+
+/// events like `focus` and `mouseenter` do not bubble, they are handled only on the target element
+pub(crate) fn bubbles(name: &str) -> bool {
+  !matches!(
+    name,
+    "focus" | "blur" | "mouseenter" | "mouseleave" | "pointerenter" | "pointerleave" | "scroll" | "load" | "error" | "toggle"
+  )
+}
+
+/// live nodes of a rendered tree, in the same shape as elements of the virtual DOM,
+/// kept by the renderer so that targets of changes are found without walking the DOM
+#[derive(Debug, Clone)]
+pub(crate) struct LiveNode<N> {
+  pub node: N,
+  pub children: Vec<LiveNode<N>>,
+}
+
+impl<N> LiveNode<N> {
+  pub fn new(node: N) -> Self {
+    Self { node, children: vec![] }
+  }
+
+  /// finds descendant by indexes of children
+  pub fn get_mut(&mut self, path: &[u32]) -> Result<&mut Self, RespoError> {
+    let mut target = self;
+    for idx in path {
+      target = target
+        .children
+        .get_mut(*idx as usize)
+        .ok_or_else(|| RespoError::Patch(format!("no live node at index {}", idx)))?;
+    }
+    Ok(target)
+  }
+}
@@ -0,0 +1,333 @@
+use std::{
+  cell::RefCell,
+  collections::{BTreeMap, HashMap},
+  fmt::Debug,
+  rc::{Rc, Weak},
+};
+
+use crate::node::{RespoEffect, RespoEffectNode, RespoEffectType, RespoEvent, RespoEventOptions};
+use crate::RespoError;
+
+use super::{bubbles, DomBackend, RespoEventCallback};
+
+/// backend that keeps nodes in memory, no browser APIs involved.
+///
+/// Effects are run with `RespoEffectNode::Memory`, and recorded in `effect_log()` as well.
+/// Events are triggered with `.trigger()`.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+  effects: Rc<RefCell<Vec<(RespoEffectType, String)>>>,
+}
+
+impl MemoryBackend {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// effects that have been run, with the type and the debug string of each effect
+  pub fn effect_log(&self) -> Vec<(RespoEffectType, String)> {
+    self.effects.borrow().to_owned()
+  }
+
+  /// returns effects that have been run and clears the log
+  pub fn take_effect_log(&self) -> Vec<(RespoEffectType, String)> {
+    self.effects.borrow_mut().drain(..).collect()
+  }
+
+  /// dispatches event of `name` from the element like a browser does, listeners are called from the element
+  /// up to the root, until one of them stops propagation with options.
+  /// `event` is cloned for every listener except the last one, so mocked events should reach only one listener.
+  ///
+  /// returns `false` when default action is prevented with options, like `dispatchEvent` in DOM
+  pub fn trigger(&self, el: &MemoryNode, name: &str, event: RespoEvent) -> Result<bool, RespoError> {
+    let mut listeners: Vec<(RespoEventOptions, RespoEventCallback)> = vec![];
+    let mut current = Some(el.to_owned());
+    while let Some(node) = current {
+      let listener = node.0.borrow().listeners.get(name).cloned();
+      if let Some((options, f)) = listener {
+        listeners.push((options, f));
+        if options.stops_propagation() {
+          break;
+        }
+      }
+      if !bubbles(name) {
+        break;
+      }
+      current = node.parent();
+    }
+    if listeners.is_empty() {
+      return Err(RespoError::MissingHandler {
+        event: name.to_owned(),
+        element: el.name(),
+      });
+    }
+
+    let prevented = listeners.iter().any(|(options, _)| options.prevents_default());
+    let last = listeners.len() - 1;
+    let mut event = Some(event);
+    for (idx, (_, f)) in listeners.iter().enumerate() {
+      let e = if idx == last { event.take() } else { event.to_owned() };
+      f(e.ok_or_else(|| RespoError::Custom(String::from("event already taken")))?)?;
+    }
+    Ok(!prevented)
+  }
+}
+
+/// handle to an element kept in memory, cloning it creates another handle to the same element.
+/// `==` compares elements by structure, listeners are compared by event names and options.
+#[derive(Clone)]
+pub struct MemoryNode(Rc<RefCell<MemoryNodeData>>);
+
+struct MemoryNodeData {
+  name: String,
+  attributes: BTreeMap<String, String>,
+  style: BTreeMap<String, String>,
+  children: Vec<MemoryNode>,
+  parent: Weak<RefCell<MemoryNodeData>>,
+  listeners: HashMap<String, (RespoEventOptions, RespoEventCallback)>,
+}
+
+impl MemoryNodeData {
+  fn drop_children(&mut self) {
+    for child in self.children.drain(..) {
+      child.0.borrow_mut().parent = Weak::new();
+    }
+  }
+}
+
+impl MemoryNode {
+  pub fn new(name: &str) -> Self {
+    Self(Rc::new(RefCell::new(MemoryNodeData {
+      name: name.to_owned(),
+      attributes: BTreeMap::new(),
+      style: BTreeMap::new(),
+      children: vec![],
+      parent: Weak::new(),
+      listeners: HashMap::new(),
+    })))
+  }
+
+  /// tag name
+  pub fn name(&self) -> String {
+    self.0.borrow().name.to_owned()
+  }
+
+  /// attributes, including properties like `innerText` and `value`. `htmlFor` is stored as `for`
+  pub fn attribute(&self, key: &str) -> Option<String> {
+    self.0.borrow().attributes.get(key).cloned()
+  }
+
+  pub fn attributes(&self) -> BTreeMap<String, String> {
+    self.0.borrow().attributes.to_owned()
+  }
+
+  pub fn style(&self, property: &str) -> Option<String> {
+    self.0.borrow().style.get(property).cloned()
+  }
+
+  pub fn children(&self) -> Vec<MemoryNode> {
+    self.0.borrow().children.to_owned()
+  }
+
+  pub fn parent(&self) -> Option<MemoryNode> {
+    self.0.borrow().parent.upgrade().map(MemoryNode)
+  }
+
+  /// names of events being listened, sorted
+  pub fn listeners(&self) -> Vec<String> {
+    let mut names = self.0.borrow().listeners.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    names
+  }
+
+  /// options of the listener of `name`
+  pub fn listener_options(&self, name: &str) -> Option<RespoEventOptions> {
+    self.0.borrow().listeners.get(name).map(|(options, _)| *options)
+  }
+
+  /// whether both handles point to the same element, like `Node.isSameNode` in DOM
+  pub fn is_same_node(&self, other: &MemoryNode) -> bool {
+    Rc::ptr_eq(&self.0, &other.0)
+  }
+
+  fn index_of(&self, child: &MemoryNode) -> Option<usize> {
+    self.0.borrow().children.iter().position(|c| Rc::ptr_eq(&c.0, &child.0))
+  }
+
+  /// detaches from previous parent, like DOM does when a node is inserted somewhere else
+  fn detach(&self) {
+    if let Some(parent) = self.parent() {
+      if let Some(idx) = parent.index_of(self) {
+        parent.0.borrow_mut().children.remove(idx);
+      }
+    }
+    self.0.borrow_mut().parent = Weak::new();
+  }
+}
+
+impl PartialEq for MemoryNode {
+  fn eq(&self, other: &Self) -> bool {
+    if Rc::ptr_eq(&self.0, &other.0) {
+      return true;
+    }
+    let a = self.0.borrow();
+    let b = other.0.borrow();
+    a.name == b.name
+      && a.attributes == b.attributes
+      && a.style == b.style
+      && self.listeners() == other.listeners()
+      && self
+        .listeners()
+        .iter()
+        .all(|name| self.listener_options(name) == other.listener_options(name))
+      && a.children == b.children
+  }
+}
+
+impl Eq for MemoryNode {}
+
+impl Debug for MemoryNode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let data = self.0.borrow();
+    let mut s = f.debug_struct("MemoryNode");
+    s.field("name", &data.name);
+    if !data.attributes.is_empty() {
+      s.field("attributes", &data.attributes);
+    }
+    if !data.style.is_empty() {
+      s.field("style", &data.style);
+    }
+    if !data.listeners.is_empty() {
+      s.field("listeners", &self.listeners());
+    }
+    if !data.children.is_empty() {
+      s.field("children", &data.children);
+    }
+    s.finish()
+  }
+}
+
+impl DomBackend for MemoryBackend {
+  type Node = MemoryNode;
+
+  fn create_element(&self, name: &str) -> Result<MemoryNode, RespoError> {
+    Ok(MemoryNode::new(name))
+  }
+
+  fn set_attribute(&self, el: &MemoryNode, key: &str, value: &str) -> Result<(), RespoError> {
+    let mut data = el.0.borrow_mut();
+    let key = match key {
+      "innerText" | "innerHTML" => {
+        // like browsers, setting content drops existing children
+        data.drop_children();
+        key
+      }
+      "htmlFor" => "for",
+      _ => key,
+    };
+    data.attributes.insert(key.to_owned(), value.to_owned());
+    Ok(())
+  }
+
+  fn remove_attribute(&self, el: &MemoryNode, key: &str) -> Result<(), RespoError> {
+    let mut data = el.0.borrow_mut();
+    let key = match key {
+      "innerText" | "innerHTML" => {
+        data.drop_children();
+        key
+      }
+      "htmlFor" => "for",
+      _ => key,
+    };
+    data.attributes.remove(key);
+    Ok(())
+  }
+
+  fn set_style(&self, el: &MemoryNode, property: &str, value: &str) -> Result<(), RespoError> {
+    el.0.borrow_mut().style.insert(property.to_owned(), value.to_owned());
+    Ok(())
+  }
+
+  fn remove_style(&self, el: &MemoryNode, property: &str) -> Result<(), RespoError> {
+    el.0.borrow_mut().style.remove(property);
+    Ok(())
+  }
+
+  fn tag_name(&self, node: &MemoryNode) -> Option<String> {
+    Some(node.name().to_ascii_lowercase())
+  }
+
+  fn get_attribute(&self, node: &MemoryNode, key: &str) -> Option<String> {
+    node.attribute(if key == "htmlFor" { "for" } else { key })
+  }
+
+  fn attribute_names(&self, el: &MemoryNode) -> Vec<String> {
+    let data = el.0.borrow();
+    let names = data.attributes.keys().filter(|k| !matches!(k.as_str(), "innerText" | "innerHTML"));
+    names.cloned().collect()
+  }
+
+  fn styles(&self, el: &MemoryNode) -> Vec<(String, String)> {
+    let data = el.0.borrow();
+    data.style.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect()
+  }
+
+  fn child_at(&self, parent: &MemoryNode, idx: u32) -> Option<MemoryNode> {
+    parent.0.borrow().children.get(idx as usize).cloned()
+  }
+
+  fn child_count(&self, parent: &MemoryNode) -> u32 {
+    parent.0.borrow().children.len() as u32
+  }
+
+  fn parent_of(&self, node: &MemoryNode) -> Option<MemoryNode> {
+    node.parent()
+  }
+
+  fn append_child(&self, parent: &MemoryNode, child: &MemoryNode) -> Result<(), RespoError> {
+    child.detach();
+    child.0.borrow_mut().parent = Rc::downgrade(&parent.0);
+    parent.0.borrow_mut().children.push(child.to_owned());
+    Ok(())
+  }
+
+  fn insert_before(&self, parent: &MemoryNode, child: &MemoryNode, reference: &MemoryNode) -> Result<(), RespoError> {
+    child.detach();
+    let idx = parent
+      .index_of(reference)
+      .ok_or_else(|| RespoError::DomInconsistent(format!("reference node is not a child of <{}>", parent.name())))?;
+    child.0.borrow_mut().parent = Rc::downgrade(&parent.0);
+    parent.0.borrow_mut().children.insert(idx, child.to_owned());
+    Ok(())
+  }
+
+  fn remove_child(&self, parent: &MemoryNode, child: &MemoryNode) -> Result<(), RespoError> {
+    let idx = parent
+      .index_of(child)
+      .ok_or_else(|| RespoError::DomInconsistent(format!("node to remove is not a child of <{}>", parent.name())))?;
+    parent.0.borrow_mut().children.remove(idx);
+    child.0.borrow_mut().parent = Weak::new();
+    Ok(())
+  }
+
+  fn attach_listener(
+    &self,
+    el: &MemoryNode,
+    name: &str,
+    options: RespoEventOptions,
+    callback: RespoEventCallback,
+  ) -> Result<(), RespoError> {
+    el.0.borrow_mut().listeners.insert(name.to_owned(), (options, callback));
+    Ok(())
+  }
+
+  fn detach_listener(&self, el: &MemoryNode, name: &str) -> Result<(), RespoError> {
+    el.0.borrow_mut().listeners.remove(name);
+    Ok(())
+  }
+
+  fn run_effect(&self, effect: &dyn RespoEffect, effect_type: RespoEffectType, el: &MemoryNode) -> Result<(), RespoError> {
+    self.effects.borrow_mut().push((effect_type, format!("{:?}", effect)));
+    effect.run(effect_type, RespoEffectNode::Memory(el))
+  }
+}
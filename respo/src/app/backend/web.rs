@@ -0,0 +1,489 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+  Document, Element, Event, FocusEvent, HtmlElement, HtmlInputElement, HtmlLabelElement, HtmlTextAreaElement, InputEvent,
+  KeyboardEvent, MouseEvent, Node,
+};
+
+use crate::node::{RespoEffect, RespoEffectNode, RespoEffectType, RespoEvent, RespoEventOptions};
+use crate::RespoError;
+
+use super::{bubbles, DomBackend, RespoEventCallback};
+use crate::app::util;
+
+/// counts backends, each backend stores ids of elements in its own property,
+/// so that apps on one page, nested ones included, do not pick handlers of each other
+static BACKEND_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// backend on top of browser DOM with `web_sys`.
+///
+/// Events are delegated, a single listener of each event type is added to the mount target,
+/// and events are dispatched to elements by walking from `event.target` up to the mount target.
+/// No handler is set on elements, so handlers from third-party code are kept.
+#[derive(Debug, Clone)]
+pub struct WebBackend {
+  document: Document,
+  delegation: Rc<Delegation>,
+}
+
+impl WebBackend {
+  /// uses document of global `window`, events are listened on `mount_target`,
+  /// which should be the one passed to the renderer
+  pub fn new(mount_target: &Node) -> Result<Self, RespoError> {
+    let window = web_sys::window().ok_or_else(|| RespoError::Dom(String::from("no global `window` exists")))?;
+    let document = window
+      .document()
+      .ok_or_else(|| RespoError::Dom(String::from("should have a document on window")))?;
+    Ok(Self {
+      document,
+      delegation: Rc::new(Delegation {
+        root: mount_target.to_owned(),
+        id_key: Rc::from(format!("__respoNodeId{}", BACKEND_COUNT.fetch_add(1, Ordering::Relaxed))),
+        registry: Rc::new(RefCell::new(Registry::default())),
+        root_listeners: RefCell::new(HashMap::new()),
+      }),
+    })
+  }
+}
+
+/// listeners on the mount target, they are removed when the last handle of the backend is dropped
+struct Delegation {
+  root: Node,
+  /// property on elements holding the id used by delegated events
+  id_key: Rc<str>,
+  registry: Rc<RefCell<Registry>>,
+  root_listeners: RefCell<HashMap<String, RootListener>>,
+}
+
+type RootListener = Closure<dyn FnMut(Event)>;
+
+/// callbacks of elements, indexed by ids stored on elements
+#[derive(Default)]
+struct Registry {
+  next_id: u32,
+  handlers: HashMap<u32, HashMap<String, (RespoEventOptions, RespoEventCallback)>>,
+}
+
+impl Debug for Delegation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut names = self.root_listeners.borrow().keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    f.debug_struct("Delegation")
+      .field("root", &self.root)
+      .field("id_key", &self.id_key)
+      .field("events", &names)
+      .field("elements", &self.registry.borrow().handlers.len())
+      .finish()
+  }
+}
+
+impl Delegation {
+  fn ensure_node_id(&self, el: &Node) -> Result<u32, RespoError> {
+    if let Some(id) = node_id(&self.id_key, el) {
+      return Ok(id);
+    }
+    let mut registry = self.registry.borrow_mut();
+    let id = registry.next_id;
+    registry.next_id += 1;
+    js_sys::Reflect::set(el, &JsValue::from_str(&self.id_key), &JsValue::from(id))
+      .map_err(|e| RespoError::Dom(format!("set node id: {:?}", e)))?;
+    Ok(id)
+  }
+
+  /// adds listener of event `name` to the root if it's not there yet,
+  /// events that do not bubble are listened in capture phase
+  fn listen(&self, name: &str) -> Result<(), RespoError> {
+    if self.root_listeners.borrow().contains_key(name) {
+      return Ok(());
+    }
+    let registry = self.registry.to_owned();
+    let id_key = self.id_key.to_owned();
+    let root = self.root.to_owned();
+    let event_name = name.to_owned();
+    let handler = Closure::wrap(Box::new(move |e: Event| {
+      dispatch_delegated(&registry, &id_key, &root, &event_name, &e);
+    }) as Box<dyn FnMut(Event)>);
+    self
+      .root
+      .add_event_listener_with_callback_and_bool(name, handler.as_ref().unchecked_ref(), !bubbles(name))
+      .map_err(|e| RespoError::Dom(format!("listen to {}: {:?}", name, e)))?;
+    self.root_listeners.borrow_mut().insert(name.to_owned(), handler);
+    Ok(())
+  }
+
+  /// drops callbacks of `node` and its descendants, which are no longer in the tree
+  fn forget_tree(&self, node: &Node) {
+    if let Some(id) = node_id(&self.id_key, node) {
+      self.registry.borrow_mut().handlers.remove(&id);
+    }
+    self.forget_descendants(node);
+  }
+
+  fn forget_descendants(&self, node: &Node) {
+    if self.registry.borrow().handlers.is_empty() {
+      return;
+    }
+    let children = node.child_nodes();
+    for idx in 0..children.length() {
+      if let Some(child) = children.item(idx) {
+        self.forget_tree(&child);
+      }
+    }
+  }
+}
+
+impl Drop for Delegation {
+  fn drop(&mut self) {
+    for (name, handler) in self.root_listeners.borrow_mut().drain() {
+      let _ = self
+        .root
+        .remove_event_listener_with_callback_and_bool(&name, handler.as_ref().unchecked_ref(), !bubbles(&name));
+    }
+  }
+}
+
+fn node_id(id_key: &str, node: &Node) -> Option<u32> {
+  js_sys::Reflect::get(node, &JsValue::from_str(id_key))
+    .ok()
+    .and_then(|v| v.as_f64())
+    .map(|v| v as u32)
+}
+
+/// calls callbacks from the target up to the root, like the DOM does with bubbling,
+/// stops when propagation is stopped by options or by a callback calling `.stop_propagation()`
+fn dispatch_delegated(registry: &RefCell<Registry>, id_key: &str, root: &Node, name: &str, event: &Event) {
+  let mut current = event.target().and_then(|t| t.dyn_into::<Node>().ok());
+  while let Some(node) = current {
+    if node.is_same_node(Some(root)) {
+      break;
+    }
+    // callback is cloned out so that handlers could trigger events synchronously
+    let listener = node_id(id_key, &node).and_then(|id| registry.borrow().handlers.get(&id).and_then(|h| h.get(name).cloned()));
+    if let Some((options, f)) = listener {
+      // applied before the handler, while the browser still waits for the result of dispatching
+      if options.prevents_default() {
+        event.prevent_default();
+      }
+      if options.stops_propagation() {
+        event.stop_propagation();
+      }
+      match to_respo_event(name, event) {
+        Ok(wrap_event) => {
+          if let Err(e) = f(wrap_event) {
+            util::error_log!("failed to handle {} event: {}", name, e);
+          }
+        }
+        Err(e) => util::warn_log!("{}", e),
+      }
+      if event.cancel_bubble() {
+        break;
+      }
+    }
+    if !bubbles(name) {
+      break;
+    }
+    current = node.parent_node();
+  }
+}
+
+/// wraps DOM event into `RespoEvent` by event type
+fn to_respo_event(name: &str, event: &Event) -> Result<RespoEvent, RespoError> {
+  match name {
+    "click" | "dblclick" => {
+      let e = event
+        .dyn_ref::<MouseEvent>()
+        .ok_or_else(|| RespoError::Dom(String::from("to mouse event")))?
+        .to_owned();
+      Ok(RespoEvent::Click {
+        client_x: e.client_x() as f64,
+        client_y: e.client_y() as f64,
+        original_event: e,
+      })
+    }
+    "input" | "change" => {
+      let target = event.target().ok_or_else(|| RespoError::Dom(String::from("get target")))?;
+      let el = target
+        .dyn_ref::<Element>()
+        .ok_or_else(|| RespoError::Dom(String::from("target to element")))?;
+      let value = match el.tag_name().as_str() {
+        "INPUT" => el
+          .dyn_ref::<HtmlInputElement>()
+          .ok_or_else(|| RespoError::Dom(String::from("to html input element")))?
+          .value(),
+        "TEXTAREA" => el
+          .dyn_ref::<HtmlTextAreaElement>()
+          .ok_or_else(|| RespoError::Dom(String::from("to html text area element")))?
+          .value(),
+        tag => return Err(RespoError::Dom(format!("unsupported {} event from element: {}", name, tag))),
+      };
+      // `change` is a plain `Event`, it's passed in the same variant as `input`
+      Ok(RespoEvent::Input {
+        value,
+        original_event: event.to_owned().unchecked_into::<InputEvent>(),
+      })
+    }
+    "keydown" | "keyup" | "keypress" => {
+      let e = event
+        .dyn_ref::<KeyboardEvent>()
+        .ok_or_else(|| RespoError::Dom(String::from("to keyboard event")))?
+        .to_owned();
+      Ok(RespoEvent::Keyboard {
+        key: e.key(),
+        key_code: e.key_code(),
+        shift_key: e.shift_key(),
+        ctrl_key: e.ctrl_key(),
+        alt_key: e.alt_key(),
+        meta_key: e.meta_key(),
+        repeat: e.repeat(),
+        original_event: e,
+      })
+    }
+    "focus" => Ok(RespoEvent::Focus(
+      event
+        .dyn_ref::<FocusEvent>()
+        .ok_or_else(|| RespoError::Dom(String::from("to focus event")))?
+        .to_owned(),
+    )),
+    "blur" => Ok(RespoEvent::Blur(
+      event
+        .dyn_ref::<FocusEvent>()
+        .ok_or_else(|| RespoError::Dom(String::from("to focus event")))?
+        .to_owned(),
+    )),
+    _ => Ok(RespoEvent::Generic {
+      name: name.to_owned(),
+      original_event: event.to_owned(),
+    }),
+  }
+}
+
+impl DomBackend for WebBackend {
+  type Node = Node;
+
+  fn create_element(&self, name: &str) -> Result<Node, RespoError> {
+    let element = self
+      .document
+      .create_element(name)
+      .map_err(|e| RespoError::Dom(format!("failed to create element {}: {:?}", name, e)))?;
+    Ok(
+      element
+        .dyn_ref::<Node>()
+        .ok_or_else(|| RespoError::Dom(String::from("converting to Node")))?
+        .to_owned(),
+    )
+  }
+
+  fn set_attribute(&self, el: &Node, key: &str, value: &str) -> Result<(), RespoError> {
+    if key == "innerText" || key == "innerHTML" {
+      self.delegation.forget_descendants(el);
+    }
+    let el = el
+      .dyn_ref::<Element>()
+      .ok_or_else(|| RespoError::Dom(String::from("load as element")))?;
+    match key {
+      "innerText" => el
+        .dyn_ref::<HtmlElement>()
+        .ok_or_else(|| RespoError::Dom(String::from("to html element")))?
+        .set_inner_text(value),
+      "innerHTML" => el.set_inner_html(value),
+      "htmlFor" => el
+        .dyn_ref::<HtmlLabelElement>()
+        .ok_or_else(|| RespoError::Dom(String::from("to label element")))?
+        .set_html_for(value),
+      "value" => match el.tag_name().as_str() {
+        "INPUT" => {
+          let input_el = el
+            .dyn_ref::<HtmlInputElement>()
+            .ok_or_else(|| RespoError::Dom(String::from("to input")))?;
+          if input_el.value() != value {
+            input_el.set_value(value);
+          }
+        }
+        "TEXTAREA" => {
+          let textarea_el = el
+            .dyn_ref::<HtmlTextAreaElement>()
+            .ok_or_else(|| RespoError::Dom(String::from("to textarea")))?;
+          if textarea_el.value() != value {
+            textarea_el.set_value(value);
+          }
+        }
+        _ => {
+          el.set_attribute(key, value)
+            .map_err(|e| RespoError::Dom(format!("failed to set attribute {}: {:?}", key, e)))?;
+        }
+      },
+      _ => {
+        el.set_attribute(key, value)
+          .map_err(|e| RespoError::Dom(format!("failed to set attribute {}: {:?}", key, e)))?;
+      }
+    }
+    Ok(())
+  }
+
+  fn remove_attribute(&self, el: &Node, key: &str) -> Result<(), RespoError> {
+    if key == "innerText" || key == "innerHTML" {
+      self.delegation.forget_descendants(el);
+    }
+    let el = el
+      .dyn_ref::<Element>()
+      .ok_or_else(|| RespoError::Dom(String::from("load as element")))?;
+    match key {
+      "innerText" => el
+        .dyn_ref::<HtmlElement>()
+        .ok_or_else(|| RespoError::Dom(String::from("to html element")))?
+        .set_inner_text(""),
+      "innerHTML" => el.set_inner_html(""),
+      "htmlFor" => el
+        .dyn_ref::<HtmlLabelElement>()
+        .ok_or_else(|| RespoError::Dom(String::from("to label element")))?
+        .set_html_for(""),
+      "value" => match el.tag_name().as_str() {
+        "INPUT" => el
+          .dyn_ref::<HtmlInputElement>()
+          .ok_or_else(|| RespoError::Dom(String::from("to input")))?
+          .set_value(""),
+        "TEXTAREA" => el
+          .dyn_ref::<HtmlTextAreaElement>()
+          .ok_or_else(|| RespoError::Dom(String::from("to textarea")))?
+          .set_value(""),
+        _ => {
+          el.remove_attribute(key)
+            .map_err(|e| RespoError::Dom(format!("failed to remove attribute {}: {:?}", key, e)))?;
+        }
+      },
+      _ => {
+        el.remove_attribute(key)
+          .map_err(|e| RespoError::Dom(format!("failed to remove attribute {}: {:?}", key, e)))?;
+      }
+    }
+    Ok(())
+  }
+
+  fn set_style(&self, el: &Node, property: &str, value: &str) -> Result<(), RespoError> {
+    let style = el
+      .dyn_ref::<HtmlElement>()
+      .ok_or_else(|| RespoError::Dom(String::from("into html element")))?
+      .style();
+    Ok(style.set_property(property, value)?)
+  }
+
+  fn remove_style(&self, el: &Node, property: &str) -> Result<(), RespoError> {
+    let style = el
+      .dyn_ref::<HtmlElement>()
+      .ok_or_else(|| RespoError::Dom(String::from("into html element")))?
+      .style();
+    style
+      .remove_property(property)
+      .map_err(|e| RespoError::Dom(format!("remove style: {:?}", e)))?;
+    Ok(())
+  }
+
+  fn tag_name(&self, node: &Node) -> Option<String> {
+    node.dyn_ref::<Element>().map(|el| el.tag_name().to_ascii_lowercase())
+  }
+
+  fn get_attribute(&self, node: &Node, key: &str) -> Option<String> {
+    if key == "innerText" {
+      // text content does not depend on layout, unlike `innerText` of `HtmlElement`
+      return node.text_content();
+    }
+    let el = node.dyn_ref::<Element>()?;
+    match key {
+      "innerHTML" => Some(el.inner_html()),
+      "htmlFor" => el.get_attribute("for"),
+      "value" => match el.tag_name().as_str() {
+        "INPUT" => el.dyn_ref::<HtmlInputElement>().map(|input| input.value()),
+        "TEXTAREA" => el.dyn_ref::<HtmlTextAreaElement>().map(|textarea| textarea.value()),
+        _ => el.get_attribute(key),
+      },
+      _ => el.get_attribute(key),
+    }
+  }
+
+  fn attribute_names(&self, el: &Node) -> Vec<String> {
+    match el.dyn_ref::<Element>() {
+      Some(el) => el.get_attribute_names().iter().filter_map(|name| name.as_string()).collect(),
+      None => vec![],
+    }
+  }
+
+  fn styles(&self, el: &Node) -> Vec<(String, String)> {
+    // parsed from the attribute, values read from `CssStyleDeclaration` are normalized by browsers
+    let rules = el.dyn_ref::<Element>().and_then(|el| el.get_attribute("style")).unwrap_or_default();
+    rules
+      .split(';')
+      .filter_map(|rule| rule.split_once(':'))
+      .map(|(property, value)| (property.trim().to_owned(), value.trim().to_owned()))
+      .collect()
+  }
+
+  fn child_at(&self, parent: &Node, idx: u32) -> Option<Node> {
+    parent.child_nodes().item(idx)
+  }
+
+  fn child_count(&self, parent: &Node) -> u32 {
+    parent.child_nodes().length()
+  }
+
+  fn parent_of(&self, node: &Node) -> Option<Node> {
+    node.parent_node()
+  }
+
+  fn append_child(&self, parent: &Node, child: &Node) -> Result<(), RespoError> {
+    parent
+      .append_child(child)
+      .map_err(|e| RespoError::DomInconsistent(format!("element appended: {:?}", e)))?;
+    Ok(())
+  }
+
+  fn insert_before(&self, parent: &Node, child: &Node, reference: &Node) -> Result<(), RespoError> {
+    parent
+      .insert_before(child, Some(reference))
+      .map_err(|e| RespoError::DomInconsistent(format!("element inserted: {:?}", e)))?;
+    Ok(())
+  }
+
+  fn remove_child(&self, parent: &Node, child: &Node) -> Result<(), RespoError> {
+    parent
+      .remove_child(child)
+      .map_err(|e| RespoError::DomInconsistent(format!("child removed: {:?}", e)))?;
+    self.delegation.forget_tree(child);
+    Ok(())
+  }
+
+  fn attach_listener(&self, el: &Node, name: &str, options: RespoEventOptions, callback: RespoEventCallback) -> Result<(), RespoError> {
+    let id = self.delegation.ensure_node_id(el)?;
+    self
+      .delegation
+      .registry
+      .borrow_mut()
+      .handlers
+      .entry(id)
+      .or_default()
+      .insert(name.to_owned(), (options, callback));
+    self.delegation.listen(name)
+  }
+
+  fn detach_listener(&self, el: &Node, name: &str) -> Result<(), RespoError> {
+    if let Some(id) = node_id(&self.delegation.id_key, el) {
+      let mut registry = self.delegation.registry.borrow_mut();
+      if let Some(handlers) = registry.handlers.get_mut(&id) {
+        handlers.remove(name);
+        if handlers.is_empty() {
+          registry.handlers.remove(&id);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn run_effect(&self, effect: &dyn RespoEffect, effect_type: RespoEffectType, el: &Node) -> Result<(), RespoError> {
+    effect.run(effect_type, RespoEffectNode::Web(el))
+  }
+}
@@ -1,11 +1,12 @@
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::rc::Rc;
 
 use crate::node::*;
+use crate::RespoError;
 
-use crate::app::util::fst;
+use crate::app::util;
 
 pub fn diff_tree<T>(
   new_tree: &RespoNode<T>,
@@ -13,21 +14,30 @@ pub fn diff_tree<T>(
   coord: &[RespoCoord],
   dom_path: &[u32],
   changes: &mut Vec<DomChange<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
   match (new_tree, old_tree) {
+    // memos are resolved by renderer in advance, rendered here when diffing trees directly
+    (RespoNode::Memo(memo), b) => {
+      diff_tree(&memo.to_component()?, b, coord, dom_path, changes)?;
+    }
+    (a, RespoNode::Memo(memo)) => {
+      diff_tree(a, &memo.to_component()?, coord, dom_path, changes)?;
+    }
     (
       RespoNode::Component(RespoComponent {
         name,
         effects,
         tree: new_child,
+        ..
       }),
       RespoNode::Component(RespoComponent {
         name: name_old,
         effects: old_effects,
         tree: old_child,
+        ..
       }),
     ) => {
       if name == name_old {
@@ -62,7 +72,7 @@ where
         changes.push(DomChange::ReplaceElement {
           coord: coord.to_owned(),
           dom_path: dom_path.to_owned(),
-          node: *new_child.to_owned(),
+          node: new_tree.to_owned(),
         });
         collect_effects_outside_in_as(new_tree, coord, dom_path, RespoEffectType::Mounted, changes)?;
       }
@@ -237,162 +247,198 @@ fn diff_style<T>(
   }
 }
 
-fn diff_event<T, U>(
-  new_event: &HashMap<Rc<str>, U>,
-  old_event: &HashMap<Rc<str>, U>,
+/// listeners with changed options are attached again, which replaces previous ones
+fn diff_event<T>(
+  new_event: &HashMap<Rc<str>, RespoListenerFn<T>>,
+  old_event: &HashMap<Rc<str>, RespoListenerFn<T>>,
   coord: &[RespoCoord],
   dom_path: &[u32],
   changes: &mut Vec<DomChange<T>>,
 ) where
   T: Debug + Clone,
 {
-  let new_keys: HashSet<Rc<str>> = new_event.keys().map(ToOwned::to_owned).collect();
-  let old_keys: HashSet<Rc<str>> = old_event.keys().map(ToOwned::to_owned).collect();
+  let add: HashSet<Rc<str>> = new_event
+    .iter()
+    .filter(|(k, listener)| old_event.get(*k) != Some(listener))
+    .map(|(k, _)| k.to_owned())
+    .collect();
+  let remove: HashSet<Rc<str>> = old_event
+    .keys()
+    .filter(|k| !new_event.contains_key(*k))
+    .map(ToOwned::to_owned)
+    .collect();
 
-  if new_keys != old_keys {
+  if !add.is_empty() || !remove.is_empty() {
     changes.push(DomChange::ModifyEvent {
       coord: coord.to_owned(),
       dom_path: dom_path.to_owned(),
-      add: new_keys.difference(&old_keys).map(ToOwned::to_owned).collect(),
-      remove: old_keys.difference(&new_keys).map(ToOwned::to_owned).collect(),
+      add,
+      remove,
     });
   }
 }
 
+/// keyed reconciliation of children. Nested changes of matched children are emitted first, with old indexes,
+/// then operations in a single `ModifyChildren`:
+///
+/// 1. removes children whose keys are gone,
+/// 2. keeps children in the longest increasing subsequence of old indexes where they are,
+/// 3. walks new children from left to right, placing each of others right after its previous sibling,
+///    by moving existing nodes or inserting new ones.
+///
+/// Duplicated keys are matched in order, extra ones are removed or inserted.
 fn diff_children<T>(
   new_children: &[(RespoIndexKey, RespoNode<T>)],
   old_children: &[(RespoIndexKey, RespoNode<T>)],
   coord: &[RespoCoord],
   dom_path: &[u32],
   changes: &mut Vec<DomChange<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
-  let mut cursor: u32 = 0;
   let mut operations: Vec<ChildDomOp<T>> = Vec::new();
-  let mut new_tracking_pointer: usize = 0;
-  let mut old_tracking_pointer: usize = 0;
-
-  loop {
-    if new_tracking_pointer >= new_children.len() {
-      if old_tracking_pointer >= old_children.len() {
-        if !operations.is_empty() {
-          changes.push(DomChange::ModifyChildren {
-            coord: coord.to_owned(),
-            dom_path: dom_path.to_owned(),
-            operations,
-          });
-        }
 
-        return Ok(());
-      } else {
-        let old_entry = &old_children[old_tracking_pointer];
-        let child_coord = vec![RespoCoord::Key(old_entry.0.to_owned())];
-        let child_dom_path = vec![cursor];
-        nested_effects_inside_out_as(
-          &old_entry.1,
-          &child_coord,
-          &child_dom_path,
-          RespoEffectType::BeforeUnmount,
-          &mut operations,
-        )?;
+  // positions of old children by keys, duplicated keys are consumed in order
+  let mut old_positions: HashMap<&RespoIndexKey, VecDeque<usize>> = HashMap::new();
+  for (idx, (k, _)) in old_children.iter().enumerate() {
+    old_positions.entry(k).or_default().push_back(idx);
+  }
+  let matched: Vec<Option<usize>> = new_children
+    .iter()
+    .map(|(k, _)| old_positions.get_mut(k).and_then(VecDeque::pop_front))
+    .collect();
+  if old_positions.len() < old_children.len() || has_duplicated_keys(new_children) {
+    util::warn_log!("duplicated keys in children at {:?}", coord);
+  }
 
-        operations.push(ChildDomOp::RemoveAt(cursor));
-        old_tracking_pointer += 1;
-      }
-    } else if old_tracking_pointer >= old_children.len() {
-      let (new_key, new_child) = &new_children[new_tracking_pointer];
-      operations.push(ChildDomOp::Append(new_key.to_owned(), new_child.to_owned()));
+  let mut kept_old = vec![false; old_children.len()];
+  for (idx, old_idx) in matched.iter().enumerate() {
+    if let Some(old_idx) = old_idx {
+      kept_old[*old_idx] = true;
+      // nested changes are applied before operations of this list, so old index is used
+      let (new_key, new_child) = &new_children[idx];
+      let mut next_coord = coord.to_owned();
+      next_coord.push(RespoCoord::Key(new_key.to_owned()));
+      let mut next_dom_path = dom_path.to_owned();
+      next_dom_path.push(*old_idx as u32);
+      diff_tree(new_child, &old_children[*old_idx].1, &next_coord, &next_dom_path, changes)?;
+    }
+  }
 
-      let child_coord = vec![RespoCoord::Key(new_key.to_owned())];
-      let child_dom_path = vec![cursor];
-      nested_effects_outside_in_as(new_child, &child_coord, &child_dom_path, RespoEffectType::Mounted, &mut operations)?;
+  // simulates the list of nodes in DOM, old nodes are marked with old indexes,
+  // inserted nodes are marked with new indexes after `old_children.len()`
+  let mut current: Vec<usize> = Vec::with_capacity(new_children.len());
+  let mut removed: u32 = 0;
+  for (old_idx, (k, old_child)) in old_children.iter().enumerate() {
+    if kept_old[old_idx] {
+      current.push(old_idx);
+    } else {
+      let idx = old_idx as u32 - removed;
+      let child_coord = vec![RespoCoord::Key(k.to_owned())];
+      nested_effects_inside_out_as(old_child, &child_coord, &[idx], RespoEffectType::BeforeUnmount, &mut operations)?;
+      operations.push(ChildDomOp::RemoveAt(idx));
+      removed += 1;
+    }
+  }
 
-      new_tracking_pointer += 1;
+  let marks: Vec<usize> = matched
+    .iter()
+    .enumerate()
+    .map(|(idx, old_idx)| old_idx.unwrap_or(old_children.len() + idx))
+    .collect();
+  let stable = longest_increasing_subsequence(&matched);
+  for (idx, (new_key, new_child)) in new_children.iter().enumerate() {
+    if stable.contains(&idx) {
+      continue;
+    }
+    // nodes before this one are in order already, place this one right after previous sibling
+    let target = if idx == 0 {
+      0
     } else {
-      let new_entry = &new_children[new_tracking_pointer];
-      let old_entry = &old_children[old_tracking_pointer];
-      if new_entry.0 == old_entry.0 {
-        let mut next_coord = coord.to_owned();
-        next_coord.push(RespoCoord::Key(new_entry.0.to_owned()));
-        let mut next_dom_path = dom_path.to_owned();
-        next_dom_path.push(cursor);
-        diff_tree(&new_entry.1, &old_entry.1, &next_coord, &next_dom_path, changes)?;
-        cursor += 1;
-        new_tracking_pointer += 1;
-        old_tracking_pointer += 1;
-      } else if Some(&new_entry.0) == old_children.get(old_tracking_pointer + 1).map(fst)
-        || Some(&new_entry.0) == old_children.get(old_tracking_pointer + 2).map(fst)
-        || Some(&new_entry.0) == old_children.get(old_tracking_pointer + 3).map(fst)
-      {
-        // look ahead for 3 entries, if still not found, regards this as a remove
-        let child_coord = vec![RespoCoord::Key(old_entry.0.to_owned())];
-        let child_dom_path = vec![cursor];
-        nested_effects_inside_out_as(
-          &old_entry.1,
-          &child_coord,
-          &child_dom_path,
-          RespoEffectType::BeforeUnmount,
-          &mut operations,
-        )?;
-        operations.push(ChildDomOp::RemoveAt(cursor));
-        old_tracking_pointer += 1;
-      } else if Some(&old_entry.0) == new_children.get(new_tracking_pointer + 1).map(fst)
-        || Some(&old_entry.0) == new_children.get(new_tracking_pointer + 2).map(fst)
-        || Some(&old_entry.0) == new_children.get(new_tracking_pointer + 3).map(fst)
-      {
-        if cursor == 0 {
-          operations.push(ChildDomOp::Prepend(new_entry.0.to_owned(), new_entry.1.to_owned()))
-        } else {
-          operations.push(ChildDomOp::InsertAfter(cursor - 1, new_entry.0.to_owned(), new_entry.1.to_owned()));
+      let prev = marks[idx - 1];
+      current
+        .iter()
+        .position(|x| *x == prev)
+        .ok_or_else(|| RespoError::Patch(String::from("previous sibling to be placed")))?
+        + 1
+    };
+    match matched[idx] {
+      Some(old_idx) => {
+        let from = current
+          .iter()
+          .position(|x| *x == old_idx)
+          .ok_or_else(|| RespoError::Patch(String::from("child to be moved")))?;
+        if from == target {
+          continue;
         }
-        let child_coord = vec![RespoCoord::Key(new_entry.0.to_owned())];
-        let child_dom_path = vec![cursor];
-        nested_effects_outside_in_as(
-          &new_entry.1,
-          &child_coord,
-          &child_dom_path,
-          RespoEffectType::Mounted,
-          &mut operations,
-        )?;
-
-        cursor += 1;
-        new_tracking_pointer += 1;
-      } else {
-        let child_coord = vec![RespoCoord::Key(old_entry.0.to_owned())];
-        let child_dom_path = vec![cursor];
-        nested_effects_inside_out_as(
-          &old_entry.1,
-          &child_coord,
-          &child_dom_path,
-          RespoEffectType::BeforeUnmount,
-          &mut operations,
-        )?;
-
-        operations.push(ChildDomOp::RemoveAt(cursor));
-        if cursor == 0 {
-          operations.push(ChildDomOp::Prepend(new_entry.0.to_owned(), new_entry.1.to_owned()))
+        current.remove(from);
+        let to = if from < target { target - 1 } else { target };
+        current.insert(to, old_idx);
+        operations.push(ChildDomOp::Move {
+          from: from as u32,
+          to: to as u32,
+        });
+      }
+      None => {
+        if target == current.len() {
+          operations.push(ChildDomOp::Append(new_key.to_owned(), new_child.to_owned()));
+        } else if target == 0 {
+          operations.push(ChildDomOp::Prepend(new_key.to_owned(), new_child.to_owned()));
         } else {
-          operations.push(ChildDomOp::InsertAfter(cursor - 1, new_entry.0.to_owned(), new_entry.1.to_owned()));
+          operations.push(ChildDomOp::InsertAfter(target as u32 - 1, new_key.to_owned(), new_child.to_owned()));
         }
+        current.insert(target, marks[idx]);
+        let child_coord = vec![RespoCoord::Key(new_key.to_owned())];
+        nested_effects_outside_in_as(new_child, &child_coord, &[target as u32], RespoEffectType::Mounted, &mut operations)?;
+      }
+    }
+  }
 
-        let child_coord = vec![RespoCoord::Key(new_entry.0.to_owned())];
-        let child_dom_path = vec![cursor];
-        nested_effects_outside_in_as(
-          &new_entry.1,
-          &child_coord,
-          &child_dom_path,
-          RespoEffectType::Mounted,
-          &mut operations,
-        )?;
+  if !operations.is_empty() {
+    changes.push(DomChange::ModifyChildren {
+      coord: coord.to_owned(),
+      dom_path: dom_path.to_owned(),
+      operations,
+    });
+  }
+  Ok(())
+}
 
-        cursor += 1;
-        new_tracking_pointer += 1;
-        old_tracking_pointer += 1;
-      }
+fn has_duplicated_keys<T>(children: &[(RespoIndexKey, RespoNode<T>)]) -> bool
+where
+  T: Debug + Clone,
+{
+  let mut keys = HashSet::with_capacity(children.len());
+  children.iter().any(|(k, _)| !keys.insert(k))
+}
+
+/// indexes of the longest increasing subsequence among matched old indexes, `None`s are skipped
+fn longest_increasing_subsequence(xs: &[Option<usize>]) -> HashSet<usize> {
+  // `tails[l]` is the index in `xs` of smallest tail of subsequences with length `l + 1`
+  let mut tails: Vec<usize> = vec![];
+  let mut prevs: Vec<Option<usize>> = vec![None; xs.len()];
+  for (idx, x) in xs.iter().enumerate() {
+    let Some(x) = x else {
+      continue;
+    };
+    let l = tails.partition_point(|t| xs[*t] < Some(*x));
+    if l > 0 {
+      prevs[idx] = Some(tails[l - 1]);
     }
+    if l == tails.len() {
+      tails.push(idx);
+    } else {
+      tails[l] = idx;
+    }
+  }
+  let mut result = HashSet::with_capacity(tails.len());
+  let mut cursor = tails.last().copied();
+  while let Some(idx) = cursor {
+    result.insert(idx);
+    cursor = prevs[idx];
   }
+  result
 }
 
 // effects at parent are collected first
@@ -402,12 +448,12 @@ pub fn collect_effects_outside_in_as<T>(
   dom_path: &[u32],
   effect_type: RespoEffectType,
   changes: &mut Vec<DomChange<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
   match tree {
-    RespoNode::Component(RespoComponent { name, effects, tree }) => {
+    RespoNode::Component(RespoComponent { name, effects, tree, .. }) => {
       if !effects.is_empty() {
         changes.push(DomChange::Effect {
           coord: coord.to_owned(),
@@ -435,6 +481,10 @@ where
       collect_effects_outside_in_as(cell, coord, dom_path, effect_type, changes)?;
       Ok(())
     }
+    RespoNode::Memo(memo) => {
+      collect_effects_outside_in_as(&memo.to_component()?, coord, dom_path, effect_type, changes)?;
+      Ok(())
+    }
   }
 }
 
@@ -445,12 +495,12 @@ pub fn collect_effects_inside_out_as<T>(
   dom_path: &[u32],
   effect_type: RespoEffectType,
   changes: &mut Vec<DomChange<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
   match tree {
-    RespoNode::Component(RespoComponent { name, effects, tree }) => {
+    RespoNode::Component(RespoComponent { name, effects, tree, .. }) => {
       let mut next_coord = coord.to_owned();
       next_coord.push(RespoCoord::Comp(name.to_owned()));
       collect_effects_inside_out_as(tree, &next_coord, dom_path, effect_type, changes)?;
@@ -479,6 +529,10 @@ where
       collect_effects_inside_out_as(cell, coord, dom_path, effect_type, changes)?;
       Ok(())
     }
+    RespoNode::Memo(memo) => {
+      collect_effects_inside_out_as(&memo.to_component()?, coord, dom_path, effect_type, changes)?;
+      Ok(())
+    }
   }
 }
 
@@ -489,12 +543,12 @@ pub(crate) fn nested_effects_outside_in_as<T>(
   dom_path: &[u32],
   effect_type: RespoEffectType,
   operations: &mut Vec<ChildDomOp<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
   match tree {
-    RespoNode::Component(RespoComponent { name, effects, tree }) => {
+    RespoNode::Component(RespoComponent { name, effects, tree, .. }) => {
       if !effects.is_empty() {
         operations.push(ChildDomOp::NestedEffect {
           nested_coord: coord.to_owned(),
@@ -509,10 +563,12 @@ where
       Ok(())
     }
     RespoNode::Element(RespoElement { children, .. }) => {
-      for (k, child) in children {
+      for (idx, (k, child)) in children.iter().enumerate() {
         let mut next_coord = coord.to_owned();
         next_coord.push(RespoCoord::Key(k.to_owned()));
-        nested_effects_outside_in_as(child, &next_coord, dom_path, effect_type, operations)?;
+        let mut next_dom_path = dom_path.to_owned();
+        next_dom_path.push(idx as u32);
+        nested_effects_outside_in_as(child, &next_coord, &next_dom_path, effect_type, operations)?;
       }
       Ok(())
     }
@@ -520,6 +576,10 @@ where
       nested_effects_outside_in_as(cell, coord, dom_path, effect_type, operations)?;
       Ok(())
     }
+    RespoNode::Memo(memo) => {
+      nested_effects_outside_in_as(&memo.to_component()?, coord, dom_path, effect_type, operations)?;
+      Ok(())
+    }
   }
 }
 
@@ -530,12 +590,12 @@ pub(crate) fn nested_effects_inside_out_as<T>(
   dom_path: &[u32],
   effect_type: RespoEffectType,
   operations: &mut Vec<ChildDomOp<T>>,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
 {
   match tree {
-    RespoNode::Component(RespoComponent { name, effects, tree }) => {
+    RespoNode::Component(RespoComponent { name, effects, tree, .. }) => {
       let mut next_coord = coord.to_owned();
       next_coord.push(RespoCoord::Comp(name.to_owned()));
       nested_effects_inside_out_as(tree, &next_coord, dom_path, effect_type, operations)?;
@@ -550,10 +610,12 @@ where
       Ok(())
     }
     RespoNode::Element(RespoElement { children, .. }) => {
-      for (k, child) in children {
+      for (idx, (k, child)) in children.iter().enumerate() {
         let mut next_coord = coord.to_owned();
         next_coord.push(RespoCoord::Key(k.to_owned()));
-        nested_effects_inside_out_as(child, &next_coord, dom_path, effect_type, operations)?;
+        let mut next_dom_path = dom_path.to_owned();
+        next_dom_path.push(idx as u32);
+        nested_effects_inside_out_as(child, &next_coord, &next_dom_path, effect_type, operations)?;
       }
       Ok(())
     }
@@ -561,5 +623,12 @@ where
       nested_effects_inside_out_as(cell, coord, dom_path, effect_type, operations)?;
       Ok(())
     }
+    RespoNode::Memo(memo) => {
+      nested_effects_inside_out_as(&memo.to_component()?, coord, dom_path, effect_type, operations)?;
+      Ok(())
+    }
   }
 }
+
+#[cfg(test)]
+mod tests;
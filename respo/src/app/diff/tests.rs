@@ -0,0 +1,1022 @@
+//! tests on `diff_tree`, changes are checked in two ways:
+//! summarized into strings and compared with expected lists,
+//! or patched into `MemoryBackend` and compared with a tree built from scratch.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::app::backend::{DomBackend, LiveNode, MemoryBackend, MemoryNode};
+use crate::app::memo::{resolve_memos, MemoCache};
+use crate::app::patch::patch_tree;
+use crate::app::renderer::{build_dom_tree, load_coord_target_tree, render_frame, RespoRenderer};
+use crate::css::respo_style;
+use crate::node::css::StyleRegistry;
+use crate::node::{
+  ChildDomOp, DispatchFn, DomChange, RespoComponent, RespoCoord, RespoEffect, RespoEffectNode, RespoEffectType, RespoElement,
+  RespoEvent, RespoEventMark, RespoEventMarkFn, RespoEventOptions, RespoIndexKey, RespoNode,
+};
+use crate::states_tree::{record_picks, RespoUpdateState};
+use crate::{div, span, RespoAction, RespoError};
+
+use super::{collect_effects_inside_out_as, collect_effects_outside_in_as, diff_tree};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EffectLabel(String);
+
+impl RespoEffect for EffectLabel {}
+
+/// actions are not dispatched in these tests, `()` is enough for renderers
+impl RespoAction for () {
+  type Intent = ();
+  fn states_action(_a: RespoUpdateState) -> Self {}
+}
+
+fn diff(new_tree: &RespoNode<()>, old_tree: &RespoNode<()>) -> Vec<DomChange<()>> {
+  let mut changes = vec![];
+  diff_tree(new_tree, old_tree, &[], &[], &mut changes).expect("diff trees");
+  changes
+}
+
+/// element with children of `<span/>`s, keyed by their texts
+fn list(keys: &[&str]) -> RespoNode<()> {
+  div()
+    .children_indexed(keys.iter().map(|k| (RespoIndexKey::from(*k), span().inner_text(*k).to_node())))
+    .to_node()
+}
+
+fn comp(name: &str, label: &str, tree: RespoElement<()>) -> RespoNode<()> {
+  RespoComponent::named(name, tree).effect(EffectLabel(label.to_owned())).to_node()
+}
+
+/// browser events are not available natively, only fields from Respo are readable
+fn mock_click() -> RespoEvent {
+  RespoEvent::Click {
+    client_x: 0.0,
+    client_y: 0.0,
+    original_event: JsValue::NULL.unchecked_into(),
+  }
+}
+
+fn coord_str(coord: &[RespoCoord]) -> String {
+  let xs = coord
+    .iter()
+    .map(|c| match c {
+      RespoCoord::Key(k) => k.to_string(),
+      RespoCoord::Comp(name) => format!("@{}", name),
+    })
+    .collect::<Vec<_>>();
+  format!("/{}", xs.join("/"))
+}
+
+fn node_str(node: &RespoNode<()>) -> String {
+  match node {
+    RespoNode::Component(RespoComponent { name, .. }) => format!("@{}", name),
+    RespoNode::Element(RespoElement { name, .. }) => format!("<{}>", name),
+    RespoNode::Referenced(cell) => node_str(cell),
+    RespoNode::Memo(memo) => format!("@{}", memo.name),
+  }
+}
+
+fn sorted<T: ToString>(xs: impl IntoIterator<Item = T>) -> String {
+  let mut ys = xs.into_iter().map(|x| x.to_string()).collect::<Vec<_>>();
+  ys.sort();
+  ys.join(",")
+}
+
+/// one line for each change, operations of children are listed in separate lines
+fn summarize(changes: &[DomChange<()>]) -> Vec<String> {
+  let mut lines = vec![];
+  for change in changes {
+    match change {
+      DomChange::ReplaceElement { dom_path, node, .. } => lines.push(format!("replace {:?} {}", dom_path, node_str(node))),
+      DomChange::ModifyAttrs { dom_path, set, unset, .. } => lines.push(format!(
+        "attrs {:?} set {} unset {}",
+        dom_path,
+        sorted(set.iter().map(|(k, v)| format!("{}={}", k, v))),
+        sorted(unset)
+      )),
+      DomChange::ModifyStyle { dom_path, set, unset, .. } => lines.push(format!(
+        "style {:?} set {} unset {}",
+        dom_path,
+        sorted(set.iter().map(|(k, v)| format!("{}={}", k, v))),
+        sorted(unset)
+      )),
+      DomChange::ModifyEvent { dom_path, add, remove, .. } => {
+        lines.push(format!("event {:?} add {} remove {}", dom_path, sorted(add), sorted(remove)))
+      }
+      DomChange::Effect {
+        coord,
+        dom_path,
+        effect_type,
+        skip_indexes,
+      } => lines.push(format!(
+        "effect {:?} {} {:?} skip {}",
+        effect_type,
+        coord_str(coord),
+        dom_path,
+        sorted(skip_indexes)
+      )),
+      DomChange::ModifyChildren { dom_path, operations, .. } => {
+        for op in operations {
+          let line = match op {
+            ChildDomOp::InsertAfter(idx, k, node) => format!("insert-after {} {} {}", idx, k, node_str(node)),
+            ChildDomOp::RemoveAt(idx) => format!("remove-at {}", idx),
+            ChildDomOp::Append(k, node) => format!("append {} {}", k, node_str(node)),
+            ChildDomOp::Prepend(k, node) => format!("prepend {} {}", k, node_str(node)),
+            ChildDomOp::Move { from, to } => format!("move {} {}", from, to),
+            ChildDomOp::NestedEffect {
+              nested_coord,
+              nested_dom_path,
+              effect_type,
+              ..
+            } => format!("effect {:?} {} {:?}", effect_type, coord_str(nested_coord), nested_dom_path),
+          };
+          lines.push(format!("children {:?} {}", dom_path, line));
+        }
+      }
+    }
+  }
+  lines
+}
+
+fn mount(backend: &MemoryBackend, tree: &RespoNode<()>, handle_event: &RespoEventMarkFn) -> (MemoryNode, LiveNode<MemoryNode>) {
+  let mount_target = backend.create_element("div").expect("create mount target");
+  let root = build_dom_tree(backend, tree, &[], handle_event.to_owned()).expect("build tree");
+  backend.append_child(&mount_target, &root.node).expect("mount");
+  (mount_target, root)
+}
+
+/// live nodes kept by renderer should be the same nodes in DOM
+fn assert_live_nodes(live: &LiveNode<MemoryNode>, node: &MemoryNode) {
+  assert!(live.node.is_same_node(node), "live node of <{}> is detached", node.name());
+  let children = node.children();
+  assert_eq!(live.children.len(), children.len(), "live children of <{}>", node.name());
+  for (live_child, child) in live.children.iter().zip(children.iter()) {
+    assert_live_nodes(live_child, child);
+  }
+}
+
+/// builds `old_tree` in memory, patches it with changes from diffing, then compares it with `new_tree` built from scratch
+fn check_patch(old_tree: &RespoNode<()>, new_tree: &RespoNode<()>) -> MemoryBackend {
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (mount_target, mut live_root) = mount(&backend, old_tree, &handle_event);
+
+  let changes = diff(new_tree, old_tree);
+  if let Err(e) = patch_tree(&backend, new_tree, old_tree, &mut live_root, &changes, handle_event.to_owned()) {
+    panic!("failed to patch: {}\nchanges: {:#?}", e, summarize(&changes));
+  }
+
+  let expected = build_dom_tree(&MemoryBackend::new(), new_tree, &[], handle_event).expect("build new tree");
+  let patched = backend.child_at(&mount_target, 0).expect("patched root");
+  assert_eq!(patched, expected.node, "changes: {:#?}", summarize(&changes));
+  assert_live_nodes(&live_root, &patched);
+  backend
+}
+
+#[test]
+fn same_trees_have_no_changes() {
+  let tree = div()
+    .class("a")
+    .style(respo_style().insert("color", "red".to_owned()))
+    .children([comp("c", "x", div()), list(&["a", "b"])])
+    .to_node();
+  assert!(diff(&tree, &tree).is_empty());
+}
+
+#[test]
+fn attrs_style_and_events() {
+  let old_tree = div()
+    .class("a")
+    .attr("title", "t")
+    .style(respo_style().insert("color", "red".to_owned()))
+    .on_click(|_, _| Ok(()))
+    .to_node();
+  let new_tree = div()
+    .class("b")
+    .style(respo_style().insert("margin", "1px".to_owned()))
+    .on_named_event("dblclick", |_, _| Ok(()))
+    .to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec![
+      "attrs [] set class=b unset title",
+      "style [] set margin=1px unset color",
+      "event [] add dblclick remove click",
+    ]
+  );
+  check_patch(&old_tree, &new_tree);
+}
+
+#[test]
+fn changed_tag_is_replaced() {
+  let old_tree = div().children([span().to_node()]).to_node();
+  let new_tree = div().children([div().to_node()]).to_node();
+  assert_eq!(summarize(&diff(&new_tree, &old_tree)), vec!["replace [0] <div>"]);
+  check_patch(&old_tree, &new_tree);
+}
+
+#[test]
+fn changed_component_is_replaced_with_effects() {
+  let old_tree = div().children([comp("a", "1", div())]).to_node();
+  let new_tree = div().children([comp("b", "2", span())]).to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec!["effect BeforeUnmount /0 [0] skip ", "replace [0] @b", "effect Mounted /0 [0] skip ",]
+  );
+  let backend = check_patch(&old_tree, &new_tree);
+  assert_eq!(
+    backend.effect_log(),
+    vec![
+      (RespoEffectType::BeforeUnmount, r#"EffectLabel("1")"#.to_owned()),
+      (RespoEffectType::Mounted, r#"EffectLabel("2")"#.to_owned()),
+    ]
+  );
+}
+
+/// records hooks called with tag names of elements from `MemoryBackend`
+#[derive(Debug, Clone, PartialEq)]
+struct EffectTagLog(Rc<RefCell<Vec<String>>>);
+
+impl EffectTagLog {
+  fn push(&self, hook: &str, el: RespoEffectNode) -> Result<(), RespoError> {
+    let el = el
+      .memory_node()
+      .ok_or_else(|| RespoError::Custom("expected a memory node".to_owned()))?;
+    self.0.borrow_mut().push(format!("{} <{}>", hook, el.name()));
+    Ok(())
+  }
+}
+
+impl RespoEffect for EffectTagLog {
+  fn mounted(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    self.push("mounted", el)
+  }
+  fn before_unmount(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    self.push("before-unmount", el)
+  }
+}
+
+#[test]
+fn memory_backend_runs_effect_hooks() {
+  let log = Rc::new(RefCell::new(vec![]));
+  let build = |name: &str, el: RespoElement<()>| RespoComponent::named(name, el).effect(EffectTagLog(log.to_owned())).to_node();
+  let old_tree = div().children([build("a", div())]).to_node();
+  let new_tree = div().children([build("b", span())]).to_node();
+  check_patch(&old_tree, &new_tree);
+  assert_eq!(log.take(), ["before-unmount <div>", "mounted <span>"]);
+}
+
+#[test]
+fn replaced_component_keeps_coord_of_listeners() {
+  let old_tree = div().children([comp("a", "1", div())]).to_node();
+  let new_tree = div().children([comp("b", "2", span().on_click(|_, _| Ok(())))]).to_node();
+
+  let backend = MemoryBackend::new();
+  let coords = Rc::new(RefCell::new(vec![]));
+  let to_coords = coords.to_owned();
+  let handle_event = RespoEventMarkFn::new(move |mark: RespoEventMark| {
+    to_coords.borrow_mut().push(coord_str(&mark.coord));
+    Ok(())
+  });
+  let (_mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+  let root = live_root.node.to_owned();
+  patch_tree(
+    &backend,
+    &new_tree,
+    &old_tree,
+    &mut live_root,
+    &diff(&new_tree, &old_tree),
+    handle_event,
+  )
+  .expect("patch");
+
+  let target = &root.children()[0];
+  backend.trigger(target, "click", mock_click()).expect("trigger click");
+  assert_eq!(coords.borrow().to_owned(), vec!["/0/@b"]);
+}
+
+#[test]
+fn named_events_are_added_and_removed() {
+  let old_tree = div()
+    .on_named_event("wheel", |_, _| Ok(()))
+    .on_named_event("pointerdown", |_, _| Ok(()))
+    .to_node();
+  let new_tree = div()
+    .on_named_event("pointerdown", |_, _| Ok(()))
+    .on_named_event("contextmenu", |_, _| Ok(()))
+    .to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec!["event [] add contextmenu remove wheel"]
+  );
+
+  let backend = MemoryBackend::new();
+  let names = Rc::new(RefCell::new(vec![]));
+  let to_names = names.to_owned();
+  let handle_event = RespoEventMarkFn::new(move |mark: RespoEventMark| {
+    if let RespoEvent::Generic { name, .. } = mark.event_info {
+      to_names.borrow_mut().push(format!("{} {}", mark.name, name));
+    }
+    Ok(())
+  });
+  let (_mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+  let changes = diff(&new_tree, &old_tree);
+  patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event).expect("patch");
+
+  let root = live_root.node;
+  assert_eq!(root.listeners(), vec!["contextmenu", "pointerdown"]);
+  let event = RespoEvent::Generic {
+    name: "contextmenu".to_owned(),
+    original_event: JsValue::NULL.unchecked_into(),
+  };
+  backend.trigger(&root, "contextmenu", event).expect("trigger contextmenu");
+  assert_eq!(names.borrow().to_owned(), vec!["contextmenu contextmenu"]);
+}
+
+#[test]
+fn event_options_are_applied_before_handlers() {
+  let build = |options: RespoEventOptions| {
+    div()
+      .on_click(|_, _| Ok(()))
+      .children([span().on_named_event_with("click", options, |_, _| Ok(())).to_node()])
+      .to_node()
+  };
+  let old_tree = build(RespoEventOptions::new());
+  let new_tree = build(RespoEventOptions::new().prevent_default().stop_propagation());
+  assert_eq!(summarize(&diff(&new_tree, &old_tree)), vec!["event [0] add click remove "]);
+  check_patch(&old_tree, &new_tree);
+
+  let backend = MemoryBackend::new();
+  let coords = Rc::new(RefCell::new(vec![]));
+  let to_coords = coords.to_owned();
+  let handle_event = RespoEventMarkFn::new(move |mark: RespoEventMark| {
+    to_coords.borrow_mut().push(coord_str(&mark.coord));
+    Ok(())
+  });
+  let (_mount_target, live_root) = mount(&backend, &new_tree, &handle_event);
+  let target = &live_root.children[0].node;
+  let not_prevented = backend.trigger(target, "click", mock_click()).expect("trigger click");
+  assert!(!not_prevented);
+  assert_eq!(coords.borrow().to_owned(), vec!["/0"]);
+}
+
+#[test]
+fn toggled_listeners_are_detached() {
+  let build = |on: bool| {
+    let el = if on {
+      span().on_click(|_, _| Ok(())).on_named_event("wheel", |_, _| Ok(()))
+    } else {
+      span()
+    };
+    div().children([el.to_node()]).to_node()
+  };
+
+  let backend = MemoryBackend::new();
+  let count = Rc::new(RefCell::new(0));
+  let to_count = count.to_owned();
+  let handle_event = RespoEventMarkFn::new(move |_| {
+    *to_count.borrow_mut() += 1;
+    Ok(())
+  });
+  let mut prev_tree = build(true);
+  let (_mount_target, mut live_root) = mount(&backend, &prev_tree, &handle_event);
+  for on in [false, true, false, false, true] {
+    let tree = build(on);
+    let changes = diff(&tree, &prev_tree);
+    patch_tree(&backend, &tree, &prev_tree, &mut live_root, &changes, handle_event.to_owned()).expect("patch");
+    prev_tree = tree;
+
+    let target = &live_root.children[0].node;
+    if on {
+      assert_eq!(target.listeners(), vec!["click", "wheel"]);
+      backend.trigger(target, "click", mock_click()).expect("trigger click");
+    } else {
+      assert!(target.listeners().is_empty());
+      assert!(matches!(
+        backend.trigger(target, "click", mock_click()),
+        Err(RespoError::MissingHandler { .. })
+      ));
+    }
+  }
+  assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn coord_errors_are_typed() {
+  let tree = comp("a", "x", div().children([span().to_node()]));
+  let found = load_coord_target_tree(&tree, &[RespoCoord::Comp("a".into()), RespoCoord::Key(0.into())]);
+  assert!(matches!(found, Ok(RespoNode::Element(RespoElement { ref name, .. })) if name.as_ref() == "span"));
+
+  for coord in [vec![RespoCoord::Comp("b".into())], vec![RespoCoord::Key(0.into())]] {
+    let found = load_coord_target_tree(&tree, &coord);
+    assert!(matches!(found, Err(RespoError::CoordMismatch(_))), "{:?}", coord);
+  }
+}
+
+#[test]
+fn boundary_renders_fallback_on_errors() {
+  let build = |broken: bool| {
+    let errors = Rc::new(RefCell::new(vec![]));
+    let tree = div()
+      .children([RespoComponent::boundary(
+        "panel",
+        || {
+          if broken {
+            Err(RespoError::Custom("broken".to_owned()))
+          } else {
+            Ok(span().inner_text("content").to_node())
+          }
+        },
+        |e| div().inner_text(e.to_string()).to_node(),
+      )
+      .on_error({
+        let errors = errors.to_owned();
+        move |e| errors.borrow_mut().push(e.to_owned())
+      })
+      .to_node()])
+      .to_node();
+    (tree, errors.take())
+  };
+
+  let (ok_tree, errors) = build(false);
+  assert!(errors.is_empty());
+  let (broken_tree, errors) = build(true);
+  assert_eq!(errors, [RespoError::Custom("broken".to_owned())]);
+  assert_eq!(crate::render_to_html(&broken_tree).expect("html"), "<div><div>broken</div></div>");
+
+  check_patch(&ok_tree, &broken_tree);
+  check_patch(&broken_tree, &ok_tree);
+}
+
+#[test]
+fn boundary_catches_errors_of_memos_inside() {
+  let errors = Rc::new(RefCell::new(vec![]));
+  let build = |broken: bool| -> RespoNode<()> {
+    let memo = RespoComponent::memo("content", broken, move || {
+      if broken {
+        Err(RespoError::Custom("broken memo".to_owned()))
+      } else {
+        Ok(span().inner_text("content").to_node())
+      }
+    });
+    div()
+      .children([RespoComponent::boundary(
+        "panel",
+        || Ok(div().children([memo]).to_node()),
+        |e| div().inner_text(e.to_string()).to_node(),
+      )
+      .on_error({
+        let errors = errors.to_owned();
+        move |e| errors.borrow_mut().push(e.to_owned())
+      })
+      .to_node()])
+      .to_node()
+  };
+  let broken = RespoError::Custom("broken memo".to_owned());
+
+  assert_eq!(
+    crate::render_to_html(&build(true)).expect("html"),
+    "<div><div>broken memo</div></div>"
+  );
+  assert_eq!(errors.take(), [broken.to_owned()]);
+
+  let mut cache = MemoCache::new();
+  let (ok_tree, mut cache) = resolve_memos(build(false), &mut cache).expect("resolve");
+  let ((broken_tree, _), picks) = record_picks(|| resolve_memos(build(true), &mut cache).expect("resolve"));
+  assert_eq!(errors.take(), [broken]);
+  assert!(picks.caught_error, "states of the failed memo are not swept");
+  assert_eq!(
+    crate::render_to_html(&broken_tree).expect("html"),
+    "<div><div>broken memo</div></div>"
+  );
+
+  check_patch(&ok_tree, &broken_tree);
+  check_patch(&broken_tree, &ok_tree);
+}
+
+#[test]
+fn tampered_dom_is_rebuilt_from_virtual_dom() {
+  let old_tree = div().children([list(&["a", "b", "c"])]).to_node();
+  let new_tree = div().children([list(&["a", "c"])]).to_node();
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+
+  // another script removes the element that is going to be removed by patching
+  let list_node = live_root.children[0].node.to_owned();
+  let b = backend.child_at(&list_node, 1).expect("child b");
+  backend.remove_child(&list_node, &b).expect("remove b");
+
+  let changes = diff(&new_tree, &old_tree);
+  patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event.to_owned()).expect("patch with recovery");
+
+  let expected = build_dom_tree(&MemoryBackend::new(), &new_tree, &[], handle_event).expect("build new tree");
+  let patched = backend.child_at(&mount_target, 0).expect("patched root");
+  assert_eq!(patched, expected.node);
+  assert_live_nodes(&live_root, &patched);
+}
+
+#[test]
+fn tampered_dom_is_rebuilt_at_moved_children() {
+  // children of `c` are changed before `c` is moved to the front, with indexes in the old tree
+  let build = |keys: &[&str], c_children: &[&str]| -> RespoNode<()> {
+    div()
+      .children_indexed(keys.iter().map(|k| {
+        let item = if *k == "c" {
+          list(c_children)
+        } else {
+          span().inner_text(*k).to_node()
+        };
+        (RespoIndexKey::from(*k), item)
+      }))
+      .to_node()
+  };
+  let old_tree = build(&["a", "b", "c"], &["c1"]);
+  let new_tree = build(&["c", "a", "b"], &[]);
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+
+  // another script removes the child of `c` that is going to be removed by patching
+  let c = live_root.children[2].node.to_owned();
+  let c1 = backend.child_at(&c, 0).expect("child of c");
+  backend.remove_child(&c, &c1).expect("remove child of c");
+
+  let changes = diff(&new_tree, &old_tree);
+  patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event.to_owned()).expect("patch with recovery");
+
+  let expected = build_dom_tree(&MemoryBackend::new(), &new_tree, &[], handle_event).expect("build new tree");
+  let patched = backend.child_at(&mount_target, 0).expect("patched root");
+  assert_eq!(patched, expected.node, "changes: {:#?}", summarize(&changes));
+  assert_live_nodes(&live_root, &patched);
+}
+
+#[test]
+fn rebuilt_subtree_unmounts_effects_before_mounting() {
+  let log = Rc::new(RefCell::new(vec![]));
+  let build = |c_children: &[&str]| -> RespoNode<()> {
+    let RespoNode::Element(list) = list(c_children) else {
+      unreachable!("list is an element")
+    };
+    let c = RespoComponent::named("c", list).effect(EffectTagLog(log.to_owned())).to_node();
+    div().children([c]).to_node()
+  };
+  let old_tree = build(&["c1"]);
+  let new_tree = build(&[]);
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (_mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+
+  let c = live_root.children[0].node.to_owned();
+  let c1 = backend.child_at(&c, 0).expect("child of c");
+  backend.remove_child(&c, &c1).expect("remove child of c");
+
+  let changes = diff(&new_tree, &old_tree);
+  patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event).expect("patch with recovery");
+  assert!(!live_root.children[0].node.is_same_node(&c), "c is rebuilt");
+  assert_eq!(log.take(), ["before-unmount <div>", "mounted <div>"]);
+}
+
+/// fails in `Mounted` with an error that backends raise for inconsistent DOM
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EffectFailing;
+
+impl RespoEffect for EffectFailing {
+  fn mounted(&self, _el: RespoEffectNode) -> Result<(), RespoError> {
+    Err(RespoError::DomInconsistent("from effect".to_owned()))
+  }
+}
+
+#[test]
+fn failed_effects_do_not_rebuild_dom() {
+  let old_tree = div().children([span().to_node()]).to_node();
+  let failing = RespoComponent::named("failing", div()).effect(EffectFailing).to_node();
+  let new_tree = div().children([span().to_node(), failing]).to_node();
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (_mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+  let first = live_root.children[0].node.to_owned();
+
+  let changes = diff(&new_tree, &old_tree);
+  let patched = patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event);
+  assert_eq!(patched, Err(RespoError::Dom("from effect".to_owned())));
+  assert!(live_root.children[0].node.is_same_node(&first), "nothing is rebuilt");
+}
+
+#[test]
+fn tampered_root_is_rebuilt_by_renderer() {
+  let backend = MemoryBackend::new();
+  let mount_target = backend.create_element("div").expect("create mount target");
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let mut renderer = RespoRenderer::mount(backend.to_owned(), mount_target.to_owned(), list(&["a", "b"]), dispatch).expect("mount");
+
+  let root = backend.child_at(&mount_target, 0).expect("root");
+  let b = backend.child_at(&root, 1).expect("child b");
+  backend.remove_child(&root, &b).expect("remove b");
+
+  renderer.update(list(&["a"])).expect("update with recovery");
+  let expected = build_dom_tree(&MemoryBackend::new(), &list(&["a"]), &[], RespoEventMarkFn::new(|_| Ok(()))).expect("build");
+  assert_eq!(mount_target.children(), vec![expected.node]);
+}
+
+/// `inner` is nested in `outer`, rendered names are recorded into `log`
+fn memo_tree(log: &Rc<RefCell<Vec<String>>>, outer: u32, sibling: u32) -> RespoNode<()> {
+  let memo_of = |name: &'static str, deps: u32, child: Option<RespoNode<()>>| {
+    let log = log.to_owned();
+    RespoComponent::memo(name, deps, move || {
+      log.borrow_mut().push(name.to_owned());
+      let mut el = div().children([span().inner_text(format!("{name} {deps}")).to_node()]);
+      if let Some(child) = &child {
+        el = el.children([child.to_owned()]);
+      }
+      Ok(el.to_node())
+    })
+  };
+  let inner = memo_of("inner", 0, None);
+  div()
+    .children([memo_of("outer", outer, Some(inner)), memo_of("sibling", sibling, None)])
+    .to_node()
+}
+
+#[test]
+fn memo_declares_styles_into_registry_of_app() {
+  let backend = MemoryBackend::new();
+  let mount_target = backend.create_element("div").expect("create mount target");
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let mut renderer = RespoRenderer::mount(backend, mount_target, div().to_node(), dispatch).expect("mount");
+
+  let styles = StyleRegistry::default();
+  let seen: Rc<RefCell<Option<StyleRegistry>>> = Rc::new(RefCell::new(None));
+  let mut view = {
+    let seen = seen.to_owned();
+    move || -> Result<RespoNode<()>, RespoError> {
+      let seen = seen.to_owned();
+      Ok(
+        div()
+          .children([RespoComponent::memo("styled", 1, move || {
+            *seen.borrow_mut() = Some(StyleRegistry::current());
+            Ok(div().to_node())
+          })])
+          .to_node(),
+      )
+    }
+  };
+  render_frame(&styles, &mut view, &mut renderer, None).expect("render");
+
+  let seen = seen.take().expect("memo rendered");
+  assert!(seen.is_same(&styles));
+}
+
+#[test]
+fn memo_skips_rendering_and_diffing_with_same_deps() {
+  let log = Rc::new(RefCell::new(vec![]));
+  let (tree0, mut cache) = resolve_memos(memo_tree(&log, 1, 1), &mut MemoCache::new()).expect("resolve");
+  assert_eq!(log.take(), ["outer", "inner", "sibling"]);
+
+  let (tree1, mut cache) = resolve_memos(memo_tree(&log, 1, 1), &mut cache).expect("resolve");
+  assert!(log.take().is_empty());
+  assert!(diff(&tree1, &tree0).is_empty());
+
+  // nested memo keeps its cache when the outer one is reused
+  let (tree2, mut cache) = resolve_memos(memo_tree(&log, 1, 2), &mut cache).expect("resolve");
+  assert_eq!(log.take(), ["sibling"]);
+  check_patch(&tree1, &tree2);
+
+  let (tree3, _) = resolve_memos(memo_tree(&log, 2, 2), &mut cache).expect("resolve");
+  assert_eq!(log.take(), ["outer"]);
+  check_patch(&tree2, &tree3);
+  assert_eq!(
+    crate::render_to_html(&tree3).expect("html"),
+    crate::render_to_html(&memo_tree(&log, 2, 2)).expect("html")
+  );
+}
+
+#[test]
+fn failed_memo_fails_html_rendering() {
+  let broken = RespoComponent::memo("broken", 0, || Err(RespoError::Custom("broken".to_owned())));
+  let tree: RespoNode<()> = div().children([span().to_node(), broken]).to_node();
+  assert_eq!(crate::render_to_html(&tree), Err(RespoError::Custom("broken".to_owned())));
+}
+
+#[test]
+fn updated_effects_skip_unchanged_args() {
+  let build = |a: &str, b: &str| {
+    RespoComponent::named("c", div())
+      .effect(EffectLabel(a.to_owned()))
+      .effect(EffectLabel(b.to_owned()))
+      .to_node()
+  };
+  assert!(diff(&build("1", "2"), &build("1", "2")).is_empty());
+  assert_eq!(
+    summarize(&diff(&build("1", "3"), &build("1", "2"))),
+    vec!["effect BeforeUpdate / [] skip 0", "effect Updated / [] skip 0"]
+  );
+  let backend = check_patch(&build("1", "2"), &build("1", "3"));
+  assert_eq!(
+    backend.effect_log(),
+    vec![
+      (RespoEffectType::BeforeUpdate, r#"EffectLabel("3")"#.to_owned()),
+      (RespoEffectType::Updated, r#"EffectLabel("3")"#.to_owned()),
+    ]
+  );
+}
+
+#[test]
+fn effects_order_of_nested_components() {
+  let tree: RespoNode<()> = comp("outer", "outer", div().children([span().to_node(), comp("inner", "inner", div())]));
+  let mut changes = vec![];
+  collect_effects_outside_in_as(&tree, &[], &[], RespoEffectType::Mounted, &mut changes).expect("collect");
+  assert_eq!(
+    summarize(&changes),
+    vec!["effect Mounted / [] skip ", "effect Mounted /@outer/1 [1] skip "]
+  );
+
+  let mut changes = vec![];
+  collect_effects_inside_out_as(&tree, &[], &[], RespoEffectType::BeforeUnmount, &mut changes).expect("collect");
+  assert_eq!(
+    summarize(&changes),
+    vec!["effect BeforeUnmount /@outer/1 [1] skip ", "effect BeforeUnmount / [] skip "]
+  );
+}
+
+#[test]
+fn nested_effects_of_inserted_children() {
+  let inserted = || comp("outer", "outer", div().children([span().to_node(), comp("inner", "inner", div())]));
+  let old_tree = div().to_node();
+  let new_tree = div().children_indexed([("a".into(), inserted())]).to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec![
+      "children [] append a @outer",
+      "children [] effect Mounted /a [0]",
+      "children [] effect Mounted /a/@outer/1 [0, 1]",
+    ]
+  );
+  check_patch(&old_tree, &new_tree);
+
+  assert_eq!(
+    summarize(&diff(&old_tree, &new_tree)),
+    vec![
+      "children [] effect BeforeUnmount /a/@outer/1 [0, 1]",
+      "children [] effect BeforeUnmount /a [0]",
+      "children [] remove-at 0",
+    ]
+  );
+  let backend = check_patch(&new_tree, &old_tree);
+  assert_eq!(
+    backend.effect_log(),
+    vec![
+      (RespoEffectType::BeforeUnmount, r#"EffectLabel("inner")"#.to_owned()),
+      (RespoEffectType::BeforeUnmount, r#"EffectLabel("outer")"#.to_owned()),
+    ]
+  );
+}
+
+#[test]
+fn children_appended_and_removed() {
+  assert_eq!(
+    summarize(&diff(&list(&["a", "b", "c"]), &list(&["a"]))),
+    vec!["children [] append b <span>", "children [] append c <span>"]
+  );
+  assert_eq!(
+    summarize(&diff(&list(&["a"]), &list(&["a", "b", "c"]))),
+    vec!["children [] remove-at 1", "children [] remove-at 1"]
+  );
+  check_patch(&list(&["a"]), &list(&["a", "b", "c"]));
+  check_patch(&list(&["a", "b", "c"]), &list(&["a"]));
+}
+
+#[test]
+fn children_inserted() {
+  assert_eq!(
+    summarize(&diff(&list(&["x", "a", "b"]), &list(&["a", "b"]))),
+    vec!["children [] prepend x <span>"]
+  );
+  assert_eq!(
+    summarize(&diff(&list(&["a", "x", "b"]), &list(&["a", "b"]))),
+    vec!["children [] insert-after 0 x <span>"]
+  );
+  assert_eq!(
+    summarize(&diff(&list(&["a", "c"]), &list(&["a", "b", "c"]))),
+    vec!["children [] remove-at 1"]
+  );
+  check_patch(&list(&["a", "b"]), &list(&["x", "a", "b"]));
+  check_patch(&list(&["a", "b"]), &list(&["a", "x", "b"]));
+  check_patch(&list(&["a", "b", "c"]), &list(&["a", "c"]));
+}
+
+#[test]
+fn children_kept_when_far_away() {
+  assert_eq!(
+    summarize(&diff(&list(&["f"]), &list(&["a", "b", "c", "d", "e", "f"]))),
+    vec![
+      "children [] remove-at 0",
+      "children [] remove-at 0",
+      "children [] remove-at 0",
+      "children [] remove-at 0",
+      "children [] remove-at 0",
+    ]
+  );
+  check_patch(&list(&["a", "b", "c", "d", "e", "f"]), &list(&["f"]));
+}
+
+#[test]
+fn children_moved_instead_of_recreated() {
+  assert_eq!(
+    summarize(&diff(&list(&["b", "a"]), &list(&["a", "b"]))),
+    vec!["children [] move 1 0"]
+  );
+  assert_eq!(
+    summarize(&diff(&list(&["b", "c", "d", "a"]), &list(&["a", "b", "c", "d"]))),
+    vec!["children [] move 0 3"]
+  );
+  assert_eq!(
+    summarize(&diff(&list(&["d", "a", "x", "c"]), &list(&["a", "b", "c", "d"]))),
+    vec![
+      "children [] remove-at 1",
+      "children [] move 2 0",
+      "children [] insert-after 1 x <span>",
+    ]
+  );
+  check_patch(&list(&["a", "b"]), &list(&["b", "a"]));
+  check_patch(&list(&["a", "b", "c", "d"]), &list(&["b", "c", "d", "a"]));
+  check_patch(&list(&["a", "b", "c", "d"]), &list(&["d", "a", "x", "c"]));
+}
+
+#[test]
+fn reversed_long_list_only_moves_nodes() {
+  let keys = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+  let old_keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+  let new_keys = old_keys.iter().rev().copied().collect::<Vec<_>>();
+  let old_tree = list(&old_keys);
+  let new_tree = list(&new_keys);
+
+  let changes = diff(&new_tree, &old_tree);
+  let lines = summarize(&changes);
+  assert_eq!(lines.len(), 999);
+  assert!(lines.iter().all(|l| l.starts_with("children [] move ")));
+
+  let backend = MemoryBackend::new();
+  let handle_event = RespoEventMarkFn::new(|_| Ok(()));
+  let (_mount_target, mut live_root) = mount(&backend, &old_tree, &handle_event);
+  let root = live_root.node.to_owned();
+  let old_nodes = root.children();
+  patch_tree(&backend, &new_tree, &old_tree, &mut live_root, &changes, handle_event).expect("patch");
+  let new_nodes = root.children();
+  for (idx, node) in new_nodes.iter().enumerate() {
+    assert!(node.is_same_node(&old_nodes[999 - idx]));
+  }
+}
+
+#[test]
+fn duplicated_keys_are_matched_in_order() {
+  check_patch(&list(&["a", "a", "b"]), &list(&["a", "b", "a"]));
+  check_patch(&list(&["a", "b", "a"]), &list(&["b", "b", "a"]));
+  check_patch(&list(&["a", "a"]), &list(&["a"]));
+}
+
+#[test]
+fn nested_changes_use_indexes_in_old_children() {
+  let old_tree = list(&["a", "b"]);
+  let new_tree = div()
+    .children_indexed([("b".into(), span().inner_text("b").class("changed").to_node())])
+    .to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec!["attrs [1] set class=changed unset ", "children [] remove-at 0"]
+  );
+  check_patch(&old_tree, &new_tree);
+
+  let new_tree = div()
+    .children_indexed([
+      ("x".into(), span().to_node()),
+      ("a".into(), span().inner_text("a").class("changed").to_node()),
+      ("b".into(), span().inner_text("b").to_node()),
+    ])
+    .to_node();
+  assert_eq!(
+    summarize(&diff(&new_tree, &old_tree)),
+    vec!["attrs [0] set class=changed unset ", "children [] prepend x <span>"]
+  );
+  check_patch(&old_tree, &new_tree);
+}
+
+#[test]
+fn inner_text_resets_children() {
+  let old_tree = div().children([span().to_node()]).to_node();
+  let new_tree = div().inner_text("text").to_node();
+  assert_eq!(summarize(&diff(&new_tree, &old_tree)), vec!["attrs [] set innerText=text unset "]);
+  check_patch(&old_tree, &new_tree);
+  assert_eq!(
+    summarize(&diff(&old_tree, &new_tree)),
+    vec!["attrs [] set  unset innerText", "children [] append 0 <span>"]
+  );
+  check_patch(&new_tree, &old_tree);
+}
+
+/// xorshift, enough for generating trees without extra dependencies
+struct Rng(u64);
+
+impl Rng {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+  fn below(&mut self, n: u64) -> u64 {
+    self.next() % n
+  }
+  fn chance(&mut self, n: u64) -> bool {
+    self.below(n) == 0
+  }
+}
+
+const KEYS: [&str; 8] = ["a", "b", "c", "d", "e", "f", "g", "h"];
+
+fn random_children(rng: &mut Rng, depth: u32) -> Vec<(RespoIndexKey, RespoNode<()>)> {
+  let mut pool = KEYS.to_vec();
+  // shuffle
+  for i in (1..pool.len()).rev() {
+    let j = rng.below(i as u64 + 1) as usize;
+    pool.swap(i, j);
+  }
+  let size = rng.below(pool.len() as u64 + 1) as usize;
+  pool
+    .into_iter()
+    .take(size)
+    .map(|k| (RespoIndexKey::from(k), random_node(rng, k, depth)))
+    .collect()
+}
+
+fn random_node(rng: &mut Rng, key: &str, depth: u32) -> RespoNode<()> {
+  let mut el = if rng.chance(4) { div() } else { span() };
+  if rng.chance(2) {
+    el = el.attr("title", format!("{}{}", key, rng.below(2)));
+  }
+  if rng.chance(2) {
+    el = el.style(respo_style().insert("color", format!("#{}", rng.below(2))));
+  }
+  if rng.chance(3) {
+    el = el.on_click(|_, _| Ok(()));
+  }
+  if depth > 0 && rng.chance(2) {
+    el = el.children_indexed(random_children(rng, depth - 1));
+  } else if rng.chance(3) {
+    el = el.inner_text(format!("{}{}", key, rng.below(2)));
+  }
+  if rng.chance(4) {
+    let name = if rng.chance(2) { "c1" } else { "c2" };
+    comp(name, &format!("{}{}", key, rng.below(2)), el)
+  } else {
+    el.to_node()
+  }
+}
+
+#[test]
+fn patched_trees_match_built_trees() {
+  let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+  for _ in 0..400 {
+    let old_tree = div().children_indexed(random_children(&mut rng, 2)).to_node();
+    let new_tree = div().children_indexed(random_children(&mut rng, 2)).to_node();
+    check_patch(&old_tree, &new_tree);
+  }
+}
+
+#[test]
+fn patched_trees_match_after_small_edits() {
+  let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+  for _ in 0..400 {
+    let old_children = random_children(&mut rng, 1);
+    // keep most of the children so that keys are matched
+    let mut new_children = old_children.to_owned();
+    for _ in 0..=rng.below(3) {
+      match rng.below(3) {
+        0 if !new_children.is_empty() => {
+          let idx = rng.below(new_children.len() as u64) as usize;
+          new_children.remove(idx);
+        }
+        1 => {
+          let key = format!("n{}", rng.below(100));
+          if new_children.iter().all(|(k, _)| k != &RespoIndexKey::from(key.as_str())) {
+            let idx = rng.below(new_children.len() as u64 + 1) as usize;
+            let node = random_node(&mut rng, &key, 1);
+            new_children.insert(idx, (key.into(), node));
+          }
+        }
+        _ if new_children.len() > 1 => {
+          let i = rng.below(new_children.len() as u64) as usize;
+          let j = rng.below(new_children.len() as u64) as usize;
+          new_children.swap(i, j);
+        }
+        _ => {}
+      }
+    }
+    let old_tree = div().children_indexed(old_children).to_node();
+    let new_tree = div().children_indexed(new_children).to_node();
+    check_patch(&old_tree, &new_tree);
+  }
+}
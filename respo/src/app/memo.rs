@@ -0,0 +1,125 @@
+use std::{collections::HashMap, fmt::Debug, rc::Rc};
+
+use crate::node::{RespoComponent, RespoCoord, RespoElement, RespoMemoDeps, RespoNode};
+use crate::states_tree::{record_picks, replay_picks, RespoStatesPicks};
+use crate::RespoError;
+
+/// subtrees rendered by memos, by coords of the memos
+pub(crate) type MemoCache<T> = HashMap<Vec<RespoCoord>, MemoEntry<T>>;
+
+pub(crate) struct MemoEntry<T>
+where
+  T: Debug + Clone,
+{
+  deps: Rc<dyn RespoMemoDeps>,
+  tree: Rc<RespoNode<T>>,
+  /// memos inside the subtree, kept along with it
+  nested: MemoCache<T>,
+  /// states picked in rendering, recorded again when the subtree is reused
+  picks: RespoStatesPicks,
+}
+
+/// replaces memos in `tree` with rendered components, returns the new tree along with the cache for next time.
+///
+/// A memo with same deps at the same coord in `prev` reuses the subtree by reference, so `diff_tree` skips it.
+/// Entries not visited are dropped.
+pub(crate) fn resolve_memos<T>(tree: RespoNode<T>, prev: &mut MemoCache<T>) -> Result<(RespoNode<T>, MemoCache<T>), RespoError>
+where
+  T: Debug + Clone,
+{
+  let mut next = HashMap::new();
+  let resolved = resolve_node(&tree, &[], prev, &mut next)?;
+  Ok((resolved.unwrap_or(tree), next))
+}
+
+/// returns `None` when there's no memo inside, so the node is kept as it is
+fn resolve_node<T>(
+  tree: &RespoNode<T>,
+  coord: &[RespoCoord],
+  prev: &mut MemoCache<T>,
+  next: &mut MemoCache<T>,
+) -> Result<Option<RespoNode<T>>, RespoError>
+where
+  T: Debug + Clone,
+{
+  match tree {
+    RespoNode::Component(RespoComponent {
+      name,
+      effects,
+      tree: child,
+      boundary,
+    }) => {
+      let mut next_coord = coord.to_owned();
+      next_coord.push(RespoCoord::Comp(name.to_owned()));
+      let resolved = match (resolve_node(child, &next_coord, prev, next), boundary) {
+        (Ok(resolved), _) => resolved.map(|child| (child, boundary.to_owned())),
+        // a memo inside a boundary failed, the fallback is rendered in place of the subtree
+        (Err(e), Some(boundary)) => {
+          let fallback = boundary.catch(name, &e);
+          let fallback = resolve_node(&fallback, &next_coord, prev, next)?.unwrap_or(fallback);
+          Some((fallback, None))
+        }
+        (Err(e), None) => return Err(e),
+      };
+      Ok(resolved.map(|(child, boundary)| {
+        RespoNode::Component(RespoComponent {
+          name: name.to_owned(),
+          effects: effects.to_owned(),
+          tree: Box::new(child),
+          boundary,
+        })
+      }))
+    }
+    RespoNode::Element(element) => {
+      let mut children = None;
+      for (idx, (k, child)) in element.children.iter().enumerate() {
+        let mut next_coord = coord.to_owned();
+        next_coord.push(RespoCoord::Key(k.to_owned()));
+        if let Some(resolved) = resolve_node(child, &next_coord, prev, next)? {
+          let xs = children.get_or_insert_with(|| element.children.to_owned());
+          xs[idx].1 = resolved;
+        }
+      }
+      Ok(children.map(|children| {
+        RespoNode::Element(RespoElement {
+          children,
+          ..element.to_owned()
+        })
+      }))
+    }
+    RespoNode::Referenced(cell) => Ok(resolve_node(cell, coord, prev, next)?.map(|x| RespoNode::Referenced(Rc::new(x)))),
+    RespoNode::Memo(memo) => {
+      let mut next_coord = coord.to_owned();
+      next_coord.push(RespoCoord::Comp(memo.name.to_owned()));
+
+      let entry = match prev.remove(&next_coord) {
+        Some(entry) if *entry.deps == *memo.deps => {
+          replay_picks(&entry.picks);
+          entry
+        }
+        old => {
+          let mut prev_nested = old.map(|entry| entry.nested).unwrap_or_default();
+          let mut nested = HashMap::new();
+          let (rendered, picks) = record_picks(|| -> Result<RespoNode<T>, RespoError> {
+            let rendered = memo.render()?;
+            Ok(resolve_node(&rendered, &next_coord, &mut prev_nested, &mut nested)?.unwrap_or(rendered))
+          });
+          MemoEntry {
+            deps: memo.deps.to_owned(),
+            tree: Rc::new(rendered?),
+            nested,
+            picks,
+          }
+        }
+      };
+      let node = RespoNode::Component(RespoComponent {
+        name: memo.name.to_owned(),
+        effects: vec![],
+        tree: Box::new(RespoNode::Referenced(entry.tree.to_owned())),
+        boundary: None,
+      });
+      next.insert(next_coord, entry);
+      Ok(Some(node))
+    }
+  }
+}
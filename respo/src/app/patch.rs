@@ -1,42 +1,33 @@
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Debug;
 
-use wasm_bindgen::prelude::Closure;
-use web_sys::{
-  Element, FocusEvent, HtmlElement, HtmlInputElement, HtmlLabelElement, HtmlTextAreaElement, InputEvent, KeyboardEvent, MouseEvent,
-  Node,
-};
+use crate::app::diff::{collect_effects_inside_out_as, collect_effects_outside_in_as};
+use crate::component::effect::RespoEffectBox;
+use crate::node::{RespoComponent, RespoEffectType, RespoElement, RespoEventMarkFn, RespoNode};
+use crate::RespoError;
 
-use wasm_bindgen::JsCast;
-use web_sys::console::warn_1;
-
-use crate::node::{RespoComponent, RespoEffectType, RespoEvent, RespoEventMark, RespoEventMarkFn, RespoNode};
-
-use super::renderer::load_coord_target_tree;
+use super::backend::{DomBackend, LiveNode};
+use super::renderer::{event_callback, load_coord_target_tree};
 use super::util;
 use crate::node::dom_change::{ChildDomOp, DomChange, RespoCoord};
 
 use crate::app::renderer::build_dom_tree;
 
-pub fn patch_tree<T>(
+/// applies changes from `diff_tree` to the rendered tree, targets are looked up from live nodes under `root`,
+/// which are updated along with the DOM
+pub fn patch_tree<T, B>(
+  backend: &B,
   tree: &RespoNode<T>,
   old_tree: &RespoNode<T>,
-  mount_target: &Node,
+  root: &mut LiveNode<B::Node>,
   changes: &[DomChange<T>],
   handle_event: RespoEventMarkFn,
-) -> Result<(), String>
+) -> Result<(), RespoError>
 where
   T: Debug + Clone,
+  B: DomBackend,
 {
-  // let el = mount_target.dyn_ref::<Element>().expect("to element");
-
-  if mount_target.child_nodes().length() != 1 {
-    return Err(format!(
-      "expected a single node under mount target, got: {:?}",
-      mount_target.child_nodes().length()
-    ));
-  }
-
   // handle BeforeUpdate before DOM changes
   for op in changes {
     if let DomChange::Effect {
@@ -47,18 +38,14 @@ where
     } = op
     {
       if effect_type == &RespoEffectType::BeforeUpdate {
-        let target = find_coord_dom_target(&mount_target.first_child().ok_or("mount position")?, op.get_dom_path())?;
+        let target = root.get_mut(op.get_dom_path())?;
         let target_tree = if effect_type == &RespoEffectType::BeforeUnmount {
           load_coord_target_tree(old_tree, coord)?
         } else {
           load_coord_target_tree(tree, coord)?
         };
         if let RespoNode::Component(RespoComponent { effects, .. }) = target_tree {
-          for (idx, effect) in effects.iter().enumerate() {
-            if !skip_indexes.contains(&(idx as u32)) {
-              effect.0.as_ref().run(effect_type.to_owned(), &target)?;
-            }
-          }
+          run_effects(backend, &effects, skip_indexes, *effect_type, &target.node)?;
         } else {
           crate::util::warn_log!("expected component for effects, got: {}", target_tree);
         }
@@ -66,487 +53,375 @@ where
     }
   }
 
+  // subtrees rebuilt after failures, later changes inside them are skipped
+  let mut rebuilt: Vec<Vec<u32>> = vec![];
   for op in changes {
-    // crate::util::log!("op: {:?}", op);
-    let target = find_coord_dom_target(&mount_target.first_child().ok_or("mount position")?, op.get_dom_path())?;
-    match op {
-      DomChange::ModifyAttrs { set, unset, .. } => {
-        let el = target.dyn_ref::<Element>().expect("load as element");
-        for (k, v) in set {
-          let k = k.as_ref();
-          if k == "innerText" {
-            el.dyn_ref::<HtmlElement>().ok_or("to html element")?.set_inner_text(v);
-          } else if k == "innerHTML" {
-            el.set_inner_html(v);
-          } else if k == "htmlFor" {
-            el.dyn_ref::<HtmlLabelElement>().ok_or("to label element")?.set_html_for(v);
-          } else if k == "value" {
-            match el.tag_name().as_str() {
-              "INPUT" => {
-                let input_el = el.dyn_ref::<HtmlInputElement>().expect("to input");
-                let prev_value = input_el.value();
-                if &prev_value != v {
-                  input_el.set_value(v);
-                }
-              }
-              "TEXTAREA" => {
-                let textarea_el = el.dyn_ref::<HtmlTextAreaElement>().expect("to textarea");
-                let prev_value = textarea_el.value();
-                if &prev_value != v {
-                  textarea_el.set_value(v);
-                }
-              }
-              name => {
-                return Err(format!("unsupported value for {}", name));
-              }
-            }
-          } else {
-            el.set_attribute(k, v).expect("to set attribute");
-          }
-        }
-        for k in unset {
-          let k = k.as_ref();
-          if k == "innerText" {
-            el.dyn_ref::<HtmlElement>().ok_or("to html element")?.set_inner_text("");
-          } else if k == "innerHTML" {
-            el.set_inner_html("");
-          } else if k == "value" {
-            let input_el = el.dyn_ref::<HtmlInputElement>().expect("to input");
-            let prev_value = input_el.value();
-            if !prev_value.is_empty() {
-              input_el.set_value("");
-            }
-          } else {
-            el.remove_attribute(k).expect("to remove attribute");
-          }
-        }
+    let dom_path = op.get_dom_path();
+    if rebuilt.iter().any(|path| dom_path.starts_with(path)) {
+      continue;
+    }
+    if let Err(e) = patch_change(backend, tree, old_tree, root, op, handle_event.to_owned()) {
+      if !e.is_dom_inconsistency() {
+        return Err(e);
       }
-      DomChange::ModifyStyle { set, unset, .. } => {
-        let style = target.dyn_ref::<HtmlElement>().expect("into html element").style();
-        for s in unset {
-          style.remove_property(s).expect("remove style");
-        }
-        for (k, v) in set {
-          style.set_property(k, v).expect("set style");
+      util::warn_log!("DOM is inconsistent at {:?}, rebuilding from virtual DOM: {}", dom_path, e);
+      rebuilt.push(recover_subtree(
+        backend,
+        tree,
+        old_tree,
+        root,
+        dom_path,
+        op.get_coord(),
+        handle_event.to_owned(),
+      )?);
+    }
+  }
+  Ok(())
+}
+
+/// applies one change, errors from DOM are returned to be recovered by the caller
+fn patch_change<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  old_tree: &RespoNode<T>,
+  root: &mut LiveNode<B::Node>,
+  op: &DomChange<T>,
+  handle_event: RespoEventMarkFn,
+) -> Result<(), RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  let target = root.get_mut(op.get_dom_path())?;
+  match op {
+    DomChange::ModifyAttrs { set, unset, .. } => {
+      for (k, v) in set {
+        backend.set_attribute(&target.node, k, v)?;
+        if is_inner_content(k) {
+          target.children.clear();
         }
       }
-      DomChange::ModifyEvent { add, remove, coord, .. } => {
-        let el = target.dyn_ref::<Element>().expect("to element");
-        for k in add.iter() {
-          attach_event(el, k, coord, handle_event.to_owned())?;
+      for k in unset {
+        backend.remove_attribute(&target.node, k)?;
+        if is_inner_content(k) {
+          target.children.clear();
         }
-        let el = el.dyn_ref::<HtmlElement>().expect("html element");
-        for k in remove {
-          match k.as_ref() {
-            "click" => {
-              el.set_onclick(None);
-            }
-            "input" => {
-              el.set_oninput(None);
-            }
-            _ => warn_1(&format!("TODO event {}", k).into()),
-          }
+      }
+    }
+    DomChange::ModifyStyle { set, unset, .. } => {
+      for s in unset {
+        backend.remove_style(&target.node, s)?;
+      }
+      for (k, v) in set {
+        backend.set_style(&target.node, k, v)?;
+      }
+    }
+    DomChange::ModifyEvent { add, remove, coord, .. } => {
+      let event = match load_coord_target_tree(tree, coord)? {
+        RespoNode::Element(RespoElement { event, .. }) => event,
+        target_tree => {
+          return Err(RespoError::CoordMismatch(format!(
+            "expected element for events, got: {}",
+            target_tree
+          )))
         }
+      };
+      for k in add.iter() {
+        let listener = event.get(k).ok_or_else(|| RespoError::MissingHandler {
+          event: k.to_string(),
+          element: format!("{:?}", coord),
+        })?;
+        backend.attach_listener(&target.node, k, listener.options, event_callback(k, coord, handle_event.to_owned()))?;
       }
-      DomChange::ReplaceElement { node, coord, .. } => {
-        let parent = target.parent_element().expect("load parent");
-        let new_element = build_dom_tree(node, coord, handle_event.to_owned()).expect("build element");
-        parent
-          .dyn_ref::<Node>()
-          .expect("to node")
-          .insert_before(&new_element, Some(&target))
-          .expect("element inserted");
-        target.dyn_ref::<Element>().expect("get node").remove();
+      for k in remove {
+        backend.detach_listener(&target.node, k)?;
       }
-      DomChange::ModifyChildren { operations, coord, .. } => {
-        let base_tree = load_coord_target_tree(tree, coord)?;
-        let old_base_tree = load_coord_target_tree(old_tree, coord)?;
-        for op in operations {
-          let handler = handle_event.to_owned();
-          match op {
-            ChildDomOp::Append(k, node) => {
-              let mut next_coord = coord.to_owned();
-              next_coord.push(RespoCoord::Key(k.to_owned()));
-              let new_element = build_dom_tree(node, &next_coord, handler).expect("new element");
-              target
-                .dyn_ref::<Node>()
-                .expect("to node")
-                .append_child(&new_element)
-                .expect("element appended");
+    }
+    DomChange::ReplaceElement { node, coord, .. } => {
+      let parent = backend
+        .parent_of(&target.node)
+        .ok_or_else(|| RespoError::DomInconsistent(String::from("element to replace is detached")))?;
+      let new_element = build_dom_tree(backend, node, coord, handle_event.to_owned())?;
+      backend.insert_before(&parent, &new_element.node, &target.node)?;
+      backend.remove_child(&parent, &target.node)?;
+      *target = new_element;
+    }
+    DomChange::ModifyChildren { operations, coord, .. } => {
+      let base_tree = load_coord_target_tree(tree, coord)?;
+      let old_base_tree = load_coord_target_tree(old_tree, coord)?;
+      for op in operations {
+        let handler = handle_event.to_owned();
+        match op {
+          ChildDomOp::Append(k, node) => {
+            let mut next_coord = coord.to_owned();
+            next_coord.push(RespoCoord::Key(k.to_owned()));
+            let new_element = build_dom_tree(backend, node, &next_coord, handler)?;
+            backend.append_child(&target.node, &new_element.node)?;
+            target.children.push(new_element);
+          }
+          ChildDomOp::Prepend(k, node) => {
+            let mut next_coord = coord.to_owned();
+            next_coord.push(RespoCoord::Key(k.to_owned()));
+            let new_element = build_dom_tree(backend, node, &next_coord, handler)?;
+            match target.children.first() {
+              None => backend.append_child(&target.node, &new_element.node)?,
+              Some(base) => backend.insert_before(&target.node, &new_element.node, &base.node)?,
             }
-            ChildDomOp::Prepend(k, node) => {
+            target.children.insert(0, new_element);
+          }
+          ChildDomOp::RemoveAt(idx) => {
+            if *idx as usize >= target.children.len() {
+              util::warn_log!("child not found at {:?}", coord);
+              return Err(RespoError::Patch(format!("child to remove not found at {}", &idx)));
+            }
+            let child = target.children.remove(*idx as usize);
+            backend.remove_child(&target.node, &child.node)?;
+          }
+          ChildDomOp::InsertAfter(idx, k, node) => {
+            let length = target.children.len() as u32;
+            if idx >= &length {
+              return Err(RespoError::Patch(format!("child to insert not found at {}", &idx)));
+            } else {
               let mut next_coord = coord.to_owned();
               next_coord.push(RespoCoord::Key(k.to_owned()));
-              let new_element = build_dom_tree(node, &next_coord, handler).expect("new element");
-              if target.child_nodes().length() == 0 {
-                target
-                  .dyn_ref::<Node>()
-                  .expect("to node")
-                  .append_child(&new_element)
-                  .expect("element appended");
-              } else {
-                let base = target.dyn_ref::<Node>().expect("to node").first_child().ok_or("to first child")?;
-                target
-                  .dyn_ref::<Node>()
-                  .expect("to node")
-                  .insert_before(&new_element, Some(&base))
-                  .expect("element appended");
+              let new_element = build_dom_tree(backend, node, &next_coord, handler)?;
+              match (idx + 1).cmp(&length) {
+                Ordering::Less => {
+                  let child = &target.children[*idx as usize + 1];
+                  backend.insert_before(&target.node, &new_element.node, &child.node)?;
+                }
+                Ordering::Equal => {
+                  backend.append_child(&target.node, &new_element.node)?;
+                }
+                Ordering::Greater => {
+                  return Err(RespoError::Patch(format!(
+                    "out of bounds: {} of {} at coord {:?}",
+                    idx, length, coord
+                  )));
+                }
               }
+              target.children.insert(*idx as usize + 1, new_element);
             }
-            ChildDomOp::RemoveAt(idx) => {
-              let child = target
-                .dyn_ref::<Element>()
-                .expect("get node")
-                .children()
-                .item(*idx)
-                .ok_or_else(|| {
-                  util::warn_log!("child not found at {:?}", coord);
-                  format!("child to remove not found at {}", &idx)
-                })?;
-              target.remove_child(&child).expect("child removed");
+          }
+          ChildDomOp::Move { from, to } => {
+            let length = target.children.len() as u32;
+            if from >= &length || to >= &length {
+              return Err(RespoError::Patch(format!(
+                "move out of bounds: {} to {} of {} at coord {:?}",
+                from, to, length, coord
+              )));
             }
-            ChildDomOp::InsertAfter(idx, k, node) => {
-              let children = target.dyn_ref::<Element>().expect("get node").children();
-              if idx >= &children.length() {
-                return Err(format!("child to insert not found at {}", &idx));
-              } else {
-                let handler = handle_event.to_owned();
-                let mut next_coord = coord.to_owned();
-                next_coord.push(RespoCoord::Key(k.to_owned()));
-                let new_element = build_dom_tree(node, &next_coord, handler).expect("new element");
-                match (idx + 1).cmp(&children.length()) {
-                  Ordering::Less => {
-                    let child = children.item(*idx + 1).ok_or_else(|| format!("child not found at {}", &idx))?;
-                    target.insert_before(&new_element, Some(&child)).expect("element inserted");
-                  }
-                  Ordering::Equal => {
-                    target.append_child(&new_element).expect("element appended");
-                  }
-                  Ordering::Greater => {
-                    return Err(format!("out of bounds: {} of {} at coord {:?}", idx, &children.length(), coord));
-                  }
-                }
-              }
+            let child = target.children.remove(*from as usize);
+            match target.children.get(*to as usize) {
+              Some(reference) => backend.insert_before(&target.node, &child.node, &reference.node)?,
+              None => backend.append_child(&target.node, &child.node)?,
             }
-            ChildDomOp::NestedEffect {
-              nested_coord,
-              nested_dom_path: nesteed_dom_path,
-              effect_type,
-              skip_indexes,
-            } => {
-              let target_tree = if effect_type == &RespoEffectType::BeforeUnmount {
-                load_coord_target_tree(&old_base_tree, nested_coord)?
-              } else {
-                load_coord_target_tree(&base_tree, nested_coord)?
-              };
-              let nested_el = find_coord_dom_target(&target, nesteed_dom_path)?;
-              if let RespoNode::Component(RespoComponent { effects, .. }) = target_tree {
-                for (idx, effect) in effects.iter().enumerate() {
-                  if !skip_indexes.contains(&(idx as u32)) {
-                    effect.0.run(effect_type.to_owned(), &nested_el)?;
-                  }
-                }
-              } else {
-                crate::util::warn_log!("expected component for effects, got: {}", target_tree);
-              }
+            target.children.insert(*to as usize, child);
+          }
+          ChildDomOp::NestedEffect {
+            nested_coord,
+            nested_dom_path: nesteed_dom_path,
+            effect_type,
+            skip_indexes,
+          } => {
+            let target_tree = if effect_type == &RespoEffectType::BeforeUnmount {
+              load_coord_target_tree(&old_base_tree, nested_coord)?
+            } else {
+              load_coord_target_tree(&base_tree, nested_coord)?
+            };
+            let nested_el = target.get_mut(nesteed_dom_path)?;
+            if let RespoNode::Component(RespoComponent { effects, .. }) = target_tree {
+              run_effects(backend, &effects, skip_indexes, *effect_type, &nested_el.node)?;
+            } else {
+              crate::util::warn_log!("expected component for effects, got: {}", target_tree);
             }
           }
         }
       }
+    }
 
-      DomChange::Effect {
-        coord,
-        effect_type,
-        skip_indexes,
-        ..
-      } => {
-        if effect_type == &RespoEffectType::BeforeUpdate {
-          // should be handled before current pass
-          continue;
-        }
-        let target_tree = if effect_type == &RespoEffectType::BeforeUnmount {
-          load_coord_target_tree(old_tree, coord)?
-        } else {
-          load_coord_target_tree(tree, coord)?
-        };
-        if let RespoNode::Component(RespoComponent { effects, .. }) = target_tree {
-          for (idx, effect) in effects.iter().enumerate() {
-            if !skip_indexes.contains(&(idx as u32)) {
-              effect.0.run(effect_type.to_owned(), &target)?;
-            }
-          }
-        } else {
-          crate::util::warn_log!("expected component for effects, got: {}", target_tree);
-        }
+    DomChange::Effect {
+      coord,
+      effect_type,
+      skip_indexes,
+      ..
+    } => {
+      if effect_type == &RespoEffectType::BeforeUpdate {
+        // should be handled before current pass
+        return Ok(());
+      }
+      let target_tree = if effect_type == &RespoEffectType::BeforeUnmount {
+        load_coord_target_tree(old_tree, coord)?
+      } else {
+        load_coord_target_tree(tree, coord)?
+      };
+      if let RespoNode::Component(RespoComponent { effects, .. }) = target_tree {
+        run_effects(backend, &effects, skip_indexes, *effect_type, &target.node)?;
+      } else {
+        crate::util::warn_log!("expected component for effects, got: {}", target_tree);
       }
     }
   }
   Ok(())
 }
 
-fn find_coord_dom_target(mount_target: &Node, coord: &[u32]) -> Result<Node, String> {
-  let mut target = mount_target.to_owned();
-  for idx in coord {
-    let child = target.child_nodes().item(idx.to_owned());
-    if child.is_none() {
-      return Err(format!("no child at index {}", &idx));
+/// rebuilds the subtree at `dom_path` from `tree`, ancestors are tried in turn when it fails.
+/// Returns the path that is rebuilt. The root element is left to the renderer, which owns the mount target.
+///
+/// `dom_path` holds indexes of live nodes, which may differ from indexes in `tree` before children are moved,
+/// so nodes are looked up in `tree` by `coord` of the change.
+fn recover_subtree<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  old_tree: &RespoNode<T>,
+  root: &mut LiveNode<B::Node>,
+  dom_path: &[u32],
+  coord: &[RespoCoord],
+  handle_event: RespoEventMarkFn,
+) -> Result<Vec<u32>, RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  let mut path = dom_path.to_owned();
+  while !path.is_empty() {
+    let rebuilt = coord_at_depth(coord, path.len())
+      .and_then(|coord| rebuild_subtree(backend, tree, old_tree, root, &path, &coord, handle_event.to_owned()));
+    match rebuilt {
+      Ok(()) => return Ok(path),
+      Err(e) => {
+        util::warn_log!("failed to rebuild at {:?}: {}", path, e);
+        path.pop();
+      }
     }
-    target = child.ok_or_else(|| format!("does not find child at index: {}", &idx))?;
   }
-  Ok(target)
+  Err(RespoError::DomInconsistent(String::from("failed to rebuild DOM below root element")))
 }
 
-pub fn attach_event(element: &Element, key: &str, coord: &[RespoCoord], handle_event: RespoEventMarkFn) -> Result<(), String> {
-  let coord = coord.to_owned();
-  // crate::util::log!("attach event {}", key);
-  match key {
-    "click" => {
-      let handler = Closure::wrap(Box::new(move |e: MouseEvent| {
-        let wrap_event = RespoEvent::Click {
-          client_x: e.client_x() as f64,
-          client_y: e.client_y() as f64,
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("click", &coord, wrap_event))
-          .expect("handle click event");
-      }) as Box<dyn FnMut(MouseEvent)>);
-      element
-        .dyn_ref::<HtmlElement>()
-        .expect("convert to html element")
-        .set_onclick(Some(handler.as_ref().unchecked_ref()));
-      handler.forget();
-    }
+/// replaces the node at `dom_path` with the one at `coord` in `tree`, `BeforeUnmount` effects of the old node
+/// are run before that, and `Mounted` effects inside the new one after that
+fn rebuild_subtree<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  old_tree: &RespoNode<T>,
+  root: &mut LiveNode<B::Node>,
+  dom_path: &[u32],
+  coord: &[RespoCoord],
+  handle_event: RespoEventMarkFn,
+) -> Result<(), RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  let (idx, parent_path) = dom_path
+    .split_last()
+    .ok_or_else(|| RespoError::Patch(String::from("root element has no parent to rebuild in")))?;
+  let idx = *idx as usize;
+  let node = load_coord_target_tree(tree, coord)?;
+  let new_child = build_dom_tree(backend, &node, coord, handle_event.to_owned())?;
+  // live node is checked before unmounting, effects are not run again when an ancestor is tried
+  root.get_mut(dom_path)?;
+  unmount_for_rebuild(backend, old_tree, root, coord, dom_path);
 
-    "dblclick" => {
-      let handler = Closure::wrap(Box::new(move |e: MouseEvent| {
-        let wrap_event = RespoEvent::Click {
-          client_x: e.client_x() as f64,
-          client_y: e.client_y() as f64,
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("dblclick", &coord, wrap_event))
-          .expect("handle dblclick event");
-      }) as Box<dyn FnMut(MouseEvent)>);
-      element
-        .dyn_ref::<HtmlElement>()
-        .expect("convert to html element")
-        .set_ondblclick(Some(handler.as_ref().unchecked_ref()));
-      handler.forget();
-    }
-    "input" => {
-      let handler = Closure::wrap(Box::new(move |e: InputEvent| {
-        let target = e.target().expect("get target");
-        let el = target.dyn_ref::<Element>().unwrap();
-        let value = match el.tag_name().as_str() {
-          "INPUT" => el.dyn_ref::<HtmlInputElement>().expect("to convert to html input element").value(),
-          "TEXTAREA" => el
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("to convert to html text area element")
-            .value(),
-          _ => {
-            // TODO Error
-            return;
-          }
-        };
-        let wrap_event = RespoEvent::Input { value, original_event: e };
-        handle_event
-          .run(RespoEventMark::new("input", &coord, wrap_event))
-          .expect("handle input event");
-      }) as Box<dyn FnMut(InputEvent)>);
-      match element.tag_name().as_str() {
-        "INPUT" => {
-          element
-            .dyn_ref::<HtmlInputElement>()
-            .expect("convert to html input element")
-            .set_oninput(Some(handler.as_ref().unchecked_ref()));
-        }
-        "TEXTAREA" => {
-          element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("convert to html textarea element")
-            .set_oninput(Some(handler.as_ref().unchecked_ref()));
-        }
-        _ => {
-          return Err(format!("unsupported input event: {}", element.tag_name()));
-        }
-      }
+  let parent = root.get_mut(parent_path)?;
+  let old_child = parent
+    .children
+    .get(idx)
+    .ok_or_else(|| RespoError::Patch(format!("no live node at {:?}", dom_path)))?;
+  // the old node might be moved or removed by other scripts
+  if let Some(old_parent) = backend.parent_of(&old_child.node) {
+    backend.remove_child(&old_parent, &old_child.node)?;
+  }
+  match parent.children.get(idx + 1) {
+    Some(next) => backend.insert_before(&parent.node, &new_child.node, &next.node)?,
+    None => backend.append_child(&parent.node, &new_child.node)?,
+  }
+  parent.children[idx] = new_child;
 
-      handler.forget();
-    }
-    "change" => {
-      let handler = Closure::wrap(Box::new(move |e: InputEvent| {
-        let wrap_event = RespoEvent::Input {
-          value: e
-            .target()
-            .expect("to reach event target")
-            .dyn_ref::<HtmlInputElement>()
-            .expect("to convert to html input element")
-            .value(),
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("change", &coord, wrap_event))
-          .expect("handle change event");
-      }) as Box<dyn FnMut(InputEvent)>);
-      match element.tag_name().as_str() {
-        "INPUT" => {
-          element
-            .dyn_ref::<HtmlInputElement>()
-            .expect("convert to html input element")
-            .set_onchange(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        "TEXTAREA" => {
-          element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("convert to html input element")
-            .set_onchange(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        _ => {
-          util::warn_log!("not handled change event for element: {}", element.tag_name());
-        }
-      }
-    }
-    "keydown" => {
-      let handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
-        // crate::util::log!("calling handler");
-        let wrap_event = RespoEvent::Keyboard {
-          key: e.key(),
-          key_code: e.key_code(),
-          shift_key: e.shift_key(),
-          ctrl_key: e.ctrl_key(),
-          alt_key: e.alt_key(),
-          meta_key: e.meta_key(),
-          repeat: e.repeat(),
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("keydown", &coord, wrap_event))
-          .expect("handle keydown event");
-      }) as Box<dyn FnMut(KeyboardEvent)>);
+  let mut changes: Vec<DomChange<T>> = vec![];
+  collect_effects_outside_in_as(&node, coord, dom_path, RespoEffectType::Mounted, &mut changes)?;
+  patch_tree(backend, tree, tree, root, &changes, handle_event)
+}
 
-      match element.tag_name().as_str() {
-        "INPUT" => {
-          element
-            .dyn_ref::<HtmlInputElement>()
-            .expect("convert to html input element")
-            .set_onkeydown(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        "TEXTAREA" => {
-          element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("convert to html input element")
-            .set_onkeydown(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        _ => {
-          util::warn_log!("not handled keydown event for element: {}", element.tag_name());
+/// runs `BeforeUnmount` effects of the node at `coord` in `old_tree`, whose DOM at `dom_path` is dropped for rebuilding.
+/// Live nodes might be patched partly, effects are skipped where nodes are not found,
+/// and failures are logged since the DOM is dropped anyway
+pub(crate) fn unmount_for_rebuild<T, B>(
+  backend: &B,
+  old_tree: &RespoNode<T>,
+  root: &mut LiveNode<B::Node>,
+  coord: &[RespoCoord],
+  dom_path: &[u32],
+) where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  // the node might be created in this render, nothing to unmount then
+  let Ok(old_node) = load_coord_target_tree(old_tree, coord) else {
+    return;
+  };
+  let mut changes: Vec<DomChange<T>> = vec![];
+  if let Err(e) = collect_effects_inside_out_as(&old_node, coord, dom_path, RespoEffectType::BeforeUnmount, &mut changes) {
+    util::warn_log!("failed to collect effects to unmount at {:?}: {}", coord, e);
+    return;
+  }
+  for change in &changes {
+    let DomChange::Effect { coord, dom_path, .. } = change else {
+      continue;
+    };
+    let ran = root
+      .get_mut(dom_path)
+      .and_then(|target| match load_coord_target_tree(old_tree, coord)? {
+        RespoNode::Component(RespoComponent { effects, .. }) => {
+          run_effects(backend, &effects, &HashSet::new(), RespoEffectType::BeforeUnmount, &target.node)
         }
-      }
+        _ => Ok(()),
+      });
+    if let Err(e) = ran {
+      util::warn_log!("failed to unmount effects at {:?} for rebuilding: {}", coord, e);
     }
-    "keyup" => {
-      let handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
-        let wrap_event = RespoEvent::Keyboard {
-          key: e.key(),
-          key_code: e.key_code(),
-          shift_key: e.shift_key(),
-          ctrl_key: e.ctrl_key(),
-          alt_key: e.alt_key(),
-          meta_key: e.meta_key(),
-          repeat: e.repeat(),
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("keyup", &coord, wrap_event))
-          .expect("handle keyup event");
-      }) as Box<dyn FnMut(KeyboardEvent)>);
-      match element.tag_name().as_str() {
-        "INPUT" => {
-          element
-            .dyn_ref::<HtmlInputElement>()
-            .expect("convert to html input element")
-            .set_onkeyup(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        "TEXTAREA" => {
-          element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("convert to html input element")
-            .set_onkeyup(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        _ => {
-          util::warn_log!("not handled keyup event for element: {}", element.tag_name());
-        }
-      }
+  }
+}
+
+/// runs effects of a component on `el`, except those at `skip_indexes`.
+/// Failures of effects are never taken as DOM inconsistencies, rebuilding DOM does not fix them
+fn run_effects<B>(
+  backend: &B,
+  effects: &[RespoEffectBox],
+  skip_indexes: &HashSet<u32>,
+  effect_type: RespoEffectType,
+  el: &B::Node,
+) -> Result<(), RespoError>
+where
+  B: DomBackend,
+{
+  for (idx, effect) in effects.iter().enumerate() {
+    if !skip_indexes.contains(&(idx as u32)) {
+      backend.run_effect(effect.0.as_ref(), effect_type, el).map_err(|e| match e {
+        RespoError::DomInconsistent(s) => RespoError::Dom(s),
+        e => e,
+      })?;
     }
-    "keypress" => {
-      let handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
-        let wrap_event = RespoEvent::Keyboard {
-          key: e.key(),
-          key_code: e.key_code(),
-          shift_key: e.shift_key(),
-          ctrl_key: e.ctrl_key(),
-          alt_key: e.alt_key(),
-          meta_key: e.meta_key(),
-          repeat: e.repeat(),
-          original_event: e,
-        };
-        handle_event
-          .run(RespoEventMark::new("keypress", &coord, wrap_event))
-          .expect("handle keypress event");
-      }) as Box<dyn FnMut(KeyboardEvent)>);
-      match element.tag_name().as_str() {
-        "INPUT" => {
-          element
-            .dyn_ref::<HtmlInputElement>()
-            .expect("convert to html input element")
-            .set_onkeypress(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        "TEXTAREA" => {
-          element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("convert to html input element")
-            .set_onkeypress(Some(handler.as_ref().unchecked_ref()));
-          handler.forget();
-        }
-        _ => {
-          util::warn_log!("not handled keypress event for element: {}", element.tag_name());
-        }
+  }
+  Ok(())
+}
+
+/// coord of the node that is `depth` levels below the root element, components wrapping the element are included.
+/// Each level of DOM takes a key in coord
+fn coord_at_depth(coord: &[RespoCoord], depth: usize) -> Result<Vec<RespoCoord>, RespoError> {
+  let mut keys = 0;
+  for (idx, branch) in coord.iter().enumerate() {
+    if let RespoCoord::Key(_) = branch {
+      keys += 1;
+      if keys == depth {
+        return Ok(coord[..=idx].to_vec());
       }
     }
-    "focus" => {
-      let handler = Closure::wrap(Box::new(move |e: FocusEvent| {
-        handle_event
-          .run(RespoEventMark::new("focus", &coord, RespoEvent::Focus(e)))
-          .expect("handle focus event");
-      }) as Box<dyn FnMut(FocusEvent)>);
-      element
-        .dyn_ref::<HtmlInputElement>()
-        .expect("convert to html input element")
-        .set_onfocus(Some(handler.as_ref().unchecked_ref()));
-      handler.forget();
-    }
-    "blur" => {
-      let handler = Closure::wrap(Box::new(move |e: FocusEvent| {
-        handle_event
-          .run(RespoEventMark::new("blur", &coord, RespoEvent::Focus(e)))
-          .expect("handle blur event");
-      }) as Box<dyn FnMut(FocusEvent)>);
-      element
-        .dyn_ref::<HtmlInputElement>()
-        .expect("convert to html input element")
-        .set_onblur(Some(handler.as_ref().unchecked_ref()));
-      handler.forget();
-    }
-    _ => {
-      warn_1(&format!("unhandled event: {}", key).into());
-    }
   }
-  Ok(())
+  Err(RespoError::CoordMismatch(format!("no node at depth {} in {:?}", depth, coord)))
+}
+
+/// DOM drops children when these properties are written
+fn is_inner_content(key: &str) -> bool {
+  key == "innerText" || key == "innerHTML"
 }
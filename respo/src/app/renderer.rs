@@ -1,155 +1,375 @@
 use crate::app::util;
-use crate::node::dom_change::RespoCoord;
+use crate::node::dom_change::{changes_to_cirru, RespoCoord};
 use crate::node::{
   DispatchFn, DomChange, RespoComponent, RespoEffectType, RespoElement, RespoEventMark, RespoEventMarkFn, RespoListenerFn, RespoNode,
 };
 use crate::warn_log;
-use std::cell::RefCell;
+use crate::RespoError;
+use std::cell::{Ref, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::rc::Rc;
-use std::sync::RwLock;
 
-use wasm_bindgen::{JsCast, JsValue};
-use web_sys::console::{error_1, warn_1};
-use web_sys::{HtmlElement, HtmlInputElement, HtmlLabelElement, HtmlTextAreaElement, Node};
+use web_sys::Node;
 
-use crate::app::diff::{collect_effects_outside_in_as, diff_tree};
-use crate::app::patch::{attach_event, patch_tree};
+use crate::app::backend::{DomBackend, LiveNode, RespoEventCallback, WebBackend};
+use crate::app::diff::{collect_effects_inside_out_as, collect_effects_outside_in_as, diff_tree};
+use crate::app::memo::{resolve_memos, MemoCache};
+use crate::app::patch::{patch_tree, unmount_for_rebuild};
+use crate::app::scheduler::RenderScheduler;
+use crate::app::trace::{now, FrameRecorder, RespoFrameTrace};
+use crate::app::RespoAppHandle;
+use crate::node::css::StyleRegistry;
+use crate::states_tree::{record_picks, RespoStatesPicks};
 
-lazy_static::lazy_static! {
-  /// event queue that code in the loop will detect
-  static ref NEED_TO_ERENDER: RwLock<bool> = RwLock::new(false);
-}
-
-/// check where need to trigger rerendering, also resets the status to false
-fn drain_rerender_status() -> bool {
-  let ret = { *NEED_TO_ERENDER.read().expect("to drain rerender status") };
-
-  if ret {
-    let mut need_to_rerender = NEED_TO_ERENDER.write().expect("to drain rerender status");
-    *need_to_rerender = false;
-  }
-  ret
-}
+#[cfg(test)]
+mod tests;
 
-pub(crate) fn mark_need_rerender() {
-  let ret = { *NEED_TO_ERENDER.read().expect("to drain rerender status") };
+/// drops branches of states tree that are not picked in a render, see `RespoApp::prune_states`
+pub(crate) type SweepStatesFn = Box<dyn Fn(&RespoStatesPicks)>;
 
-  if !ret {
-    let mut need_to_erender = NEED_TO_ERENDER.write().expect("to drain rerender status");
-    *need_to_erender = true;
+/// calls `f` to render, states picked inside are swept with `sweep` when rendering succeeds
+fn sweeping_states<R>(sweep: &Option<SweepStatesFn>, f: impl FnOnce() -> Result<R, RespoError>) -> Result<R, RespoError> {
+  match sweep {
+    Some(sweep) => {
+      let (ret, picks) = record_picks(f);
+      if ret.is_ok() {
+        sweep(&picks);
+      }
+      ret
+    }
+    None => f(),
   }
 }
 
-/// render elements
-pub(crate) fn render_node<T, U>(
+/// render elements, with `hydrate` enabled, existing DOM under mount target is reused,
+/// and mismatches between DOM and virtual tree are returned along with the handle.
+/// Later renders are run by `scheduler`, which is triggered by dispatching, and traced into `recorder` if there's one.
+/// States not picked in a render are dropped by `sweep_states` if there's one.
+pub(crate) fn render_node<T>(
   mount_target: Node,
-  // TODO it copies the whole store, need to optimize
-  get_store: Box<dyn Fn() -> U>,
-  mut renderer: Box<dyn FnMut() -> Result<RespoNode<T>, String>>,
+  mut renderer: Box<dyn FnMut() -> Result<RespoNode<T>, RespoError>>,
   dispatch_action: DispatchFn<T>,
-  interval: Option<i32>,
-) -> Result<(), JsValue>
+  scheduler: RenderScheduler,
+  recorder: Option<FrameRecorder<T>>,
+  sweep_states: Option<SweepStatesFn>,
+  hydrate: bool,
+) -> Result<(RespoAppHandle, Vec<String>), RespoError>
 where
   T: 'static + Debug + Clone,
-  U: Debug + Clone + 'static,
 {
-  let prev_store = RefCell::new(get_store());
-  let tree0: RespoNode<T> = renderer()?;
-  let prev_tree = Rc::new(RefCell::new(tree0.to_owned()));
-
-  let to_prev_tree = prev_tree.to_owned();
-  let handle_event = RespoEventMarkFn::new(move |mark: RespoEventMark| -> Result<(), String> {
-    match request_for_target_handler(&to_prev_tree.borrow(), &mark.name, &mark.coord) {
-      Ok(handler) => match handler.run(mark.event_info, dispatch_action.to_owned()) {
-        Ok(()) => {
-          // util::log!("finished event: {} {:?}", mark.name, mark.coord);
-          mark_need_rerender();
-        }
-        Err(e) => {
-          error_1(&format!("event handler error: {:?}", e).into());
-        }
-      },
-      Err(msg) => {
-        error_1(&format!("event not handled: {}", msg).into());
+  // styles declared in views are registered to this app
+  let styles = StyleRegistry::new(&mount_target);
+  let (respo_renderer, mismatches) = sweeping_states(&sweep_states, || {
+    // memos are rendered by the renderer, styles declared inside also go to this app
+    styles.enter(|| {
+      let tree0: RespoNode<T> = renderer()?;
+      let backend = WebBackend::new(&mount_target)?;
+      if hydrate {
+        RespoRenderer::hydrate(backend, mount_target, tree0, dispatch_action)
+      } else {
+        Ok((RespoRenderer::mount(backend, mount_target, tree0, dispatch_action)?, vec![]))
       }
+    })
+  })?;
+  // shared with the handle, which takes it out when unmounting
+  let respo_renderer = Rc::new(RefCell::new(Some(respo_renderer)));
+
+  let teardown = {
+    let respo_renderer = respo_renderer.to_owned();
+    let styles = styles.to_owned();
+    move || -> Result<(), RespoError> {
+      let prev = respo_renderer
+        .try_borrow_mut()
+        .map_err(|_| RespoError::Custom(String::from("failed to unmount during rendering")))?
+        .take();
+      if let Some(prev) = prev {
+        prev.unmount()?;
+      }
+      styles.clear();
+      Ok(())
     }
+  };
 
-    Ok(())
-  });
-
-  let handler = handle_event.to_owned();
-  let element = build_dom_tree(&tree0, &[], handler)?;
-
-  // collection mounted effects
-  let mut mount_changes: Vec<DomChange<T>> = vec![];
-  collect_effects_outside_in_as(&tree0, &[], &[], RespoEffectType::Mounted, &mut mount_changes)?;
-
-  mount_target.append_child(&element)?;
-  let handler = handle_event.to_owned();
-  // util::log!("mounted changed: {:?}", mount_changes);
-  patch_tree(&tree0, &prev_tree.borrow(), &mount_target, &mount_changes, handler)?;
-
-  let to_prev_tree = prev_tree.to_owned();
-  match interval {
-    Some(v) => {
-      util::raf_loop_slow(
-        v,
-        Box::new(move || -> Result<(), String> {
-          if drain_rerender_status() {
-            let store = get_store();
-            // if store == prev_store.borrow().to_owned() {
-            //   // no need to update if store not changed
-            //   return Ok(());
-            // }
-
-            prev_store.replace(store);
-
-            let new_tree = renderer()?;
-            let mut changes: Vec<DomChange<T>> = vec![];
-            diff_tree(&new_tree, &to_prev_tree.borrow(), &Vec::new(), &Vec::new(), &mut changes)?;
-
-            // use cirru_parser::CirruWriterOptions;
-            // util::log!(
-            //   "prev tree: {}",
-            //   cirru_parser::format(&[to_prev_tree.borrow().to_owned().into()], CirruWriterOptions { use_inline: true }).unwrap()
-            // );
-            // use crate::dom_change::changes_to_cirru;
-            // util::log!(
-            //   "changes: {}",
-            //   cirru_parser::format(&[changes_to_cirru(&changes)], CirruWriterOptions { use_inline: true }).unwrap()
-            // );
-
-            let handler = handle_event.to_owned();
-            patch_tree(&new_tree, &prev_tree.borrow(), &mount_target, &changes, handler)?;
-            prev_tree.replace(new_tree);
-          }
+  scheduler.set_render(Box::new(move || -> Result<(), RespoError> {
+    sweeping_states(&sweep_states, || {
+      let mut borrowed = respo_renderer.borrow_mut();
+      match borrowed.as_mut() {
+        Some(r) => render_frame(&styles, &mut renderer, r, recorder.as_ref()),
+        None => Ok(()),
+      }
+    })
+  }));
 
-          Ok(())
-        }),
-      );
-    }
-    None => {
-      util::raf_loop(Box::new(move || -> Result<(), String> {
-        if drain_rerender_status() {
-          let new_tree = renderer()?;
-          let mut changes: Vec<DomChange<T>> = vec![];
-          diff_tree(&new_tree, &to_prev_tree.borrow(), &Vec::new(), &Vec::new(), &mut changes)?;
-
-          let handler = handle_event.to_owned();
-          patch_tree(&new_tree, &prev_tree.borrow(), &mount_target, &changes, handler)?;
-          prev_tree.replace(new_tree);
-        }
+  Ok((RespoAppHandle::new(scheduler, Box::new(teardown)), mismatches))
+}
 
+/// calls view and updates the renderer with the tree, both inside `styles`, so that styles declared
+/// in view and in memos rendered by the renderer go to the same app
+pub(crate) fn render_frame<T, B>(
+  styles: &StyleRegistry,
+  renderer: &mut dyn FnMut() -> Result<RespoNode<T>, RespoError>,
+  r: &mut RespoRenderer<T, B>,
+  recorder: Option<&FrameRecorder<T>>,
+) -> Result<(), RespoError>
+where
+  T: Debug + Clone + 'static,
+  B: DomBackend,
+{
+  styles.enter(|| {
+    let started = now();
+    let new_tree = renderer()?;
+    match recorder {
+      Some(recorder) => {
+        let mut trace = RespoFrameTrace::new(recorder.take_actions());
+        trace.view_time = now() - started;
+        r.update_traced(new_tree, &mut trace)?;
+        recorder.tracer.trace(&trace);
         Ok(())
-      }));
+      }
+      None => r.update(new_tree),
     }
+  })
+}
+
+/// keeps a virtual DOM tree rendered into `mount_target` of a DOM backend,
+/// later trees are diffed against the previous one and patched into the DOM.
+///
+/// Events from the DOM are routed to listeners of the latest tree, and actions are sent to `dispatch`.
+///
+/// ```rust
+/// use respo::{div, span, DispatchFn, DomBackend, MemoryBackend, RespoAction, RespoRenderer};
+/// use respo::states_tree::RespoUpdateState;
+///
+/// #[derive(Debug, Clone)]
+/// struct Noop;
+/// impl RespoAction for Noop {
+///   type Intent = ();
+///   fn states_action(_a: RespoUpdateState) -> Self {
+///     Noop
+///   }
+/// }
+///
+/// let backend = MemoryBackend::new();
+/// let mount_target = backend.create_element("div").unwrap();
+/// let dispatch = DispatchFn::new(|_: Noop| Ok(()));
+/// let tree = div().children([span().inner_text("a").to_node()]).to_node();
+/// let mut renderer = RespoRenderer::mount(backend, mount_target.to_owned(), tree, dispatch).unwrap();
+///
+/// let tree = div().children([span().inner_text("b").to_node()]).to_node();
+/// renderer.update(tree).unwrap();
+/// let root = &renderer.mount_target().children()[0];
+/// assert_eq!(root.children()[0].attribute("innerText").as_deref(), Some("b"));
+///
+/// renderer.unmount().unwrap();
+/// assert!(mount_target.children().is_empty());
+/// ```
+pub struct RespoRenderer<T, B>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  backend: B,
+  mount_target: B::Node,
+  /// live nodes of the element under `mount_target`
+  root: LiveNode<B::Node>,
+  prev_tree: Rc<RefCell<RespoNode<T>>>,
+  /// subtrees of memos in previous tree
+  memos: MemoCache<T>,
+  handle_event: RespoEventMarkFn,
+}
+
+impl<T, B> Debug for RespoRenderer<T, B>
+where
+  T: Debug + Clone,
+  B: DomBackend + Debug,
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RespoRenderer")
+      .field("backend", &self.backend)
+      .field("mount_target", &self.mount_target)
+      .finish()
+  }
+}
+
+impl<T, B> RespoRenderer<T, B>
+where
+  T: Debug + Clone + 'static,
+  B: DomBackend,
+{
+  /// builds DOM of `tree` and appends it to `mount_target`, `Mounted` effects are called after that
+  pub fn mount(backend: B, mount_target: B::Node, tree: RespoNode<T>, dispatch: DispatchFn<T>) -> Result<Self, RespoError> {
+    let (tree, memos) = resolve_memos(tree, &mut MemoCache::new())?;
+    let prev_tree = Rc::new(RefCell::new(tree));
+    let handle_event = route_events(prev_tree.to_owned(), dispatch);
+    let root = build_dom_tree(&backend, &prev_tree.borrow(), &[], handle_event.to_owned())?;
+    backend.append_child(&mount_target, &root.node)?;
+    let mut respo_renderer = Self {
+      backend,
+      mount_target,
+      root,
+      prev_tree,
+      memos,
+      handle_event,
+    };
+    respo_renderer.run_mounted_effects()?;
+    Ok(respo_renderer)
+  }
+
+  /// reuses DOM under `mount_target` instead of building it, returns mismatches that were fixed
+  pub(crate) fn hydrate(
+    backend: B,
+    mount_target: B::Node,
+    tree: RespoNode<T>,
+    dispatch: DispatchFn<T>,
+  ) -> Result<(Self, Vec<String>), RespoError> {
+    let (tree, memos) = resolve_memos(tree, &mut MemoCache::new())?;
+    let prev_tree = Rc::new(RefCell::new(tree));
+    let handle_event = route_events(prev_tree.to_owned(), dispatch);
+    let mut mismatches: Vec<String> = vec![];
+    let root = hydrate_mount_target(
+      &backend,
+      &prev_tree.borrow(),
+      &mount_target,
+      handle_event.to_owned(),
+      &mut mismatches,
+    )?;
+    let mut respo_renderer = Self {
+      backend,
+      mount_target,
+      root,
+      prev_tree,
+      memos,
+      handle_event,
+    };
+    respo_renderer.run_mounted_effects()?;
+    Ok((respo_renderer, mismatches))
+  }
+
+  fn run_mounted_effects(&mut self) -> Result<(), RespoError> {
+    let tree = self.prev_tree.borrow();
+    let mut mount_changes: Vec<DomChange<T>> = vec![];
+    collect_effects_outside_in_as(&tree, &[], &[], RespoEffectType::Mounted, &mut mount_changes)?;
+    // util::log!("mounted changed: {:?}", mount_changes);
+    patch_tree(
+      &self.backend,
+      &tree,
+      &tree,
+      &mut self.root,
+      &mount_changes,
+      self.handle_event.to_owned(),
+    )
   }
 
-  Ok(())
+  /// diffs `tree` against the previous tree and patches the DOM
+  pub fn update(&mut self, tree: RespoNode<T>) -> Result<(), RespoError> {
+    self.update_with(tree, None)
+  }
+
+  /// like `update`, changes and times of each step are written into `trace`
+  pub fn update_traced(&mut self, tree: RespoNode<T>, trace: &mut RespoFrameTrace<T>) -> Result<(), RespoError> {
+    self.update_with(tree, Some(trace))
+  }
+
+  fn update_with(&mut self, tree: RespoNode<T>, trace: Option<&mut RespoFrameTrace<T>>) -> Result<(), RespoError> {
+    let started = now();
+    let (tree, memos) = resolve_memos(tree, &mut self.memos)?;
+    let resolved = now();
+    let prev_tree = self.prev_tree.borrow();
+    let mut changes: Vec<DomChange<T>> = vec![];
+    diff_tree(&tree, &prev_tree, &Vec::new(), &Vec::new(), &mut changes)?;
+    let diffed = now();
+
+    let patched = patch_tree(
+      &self.backend,
+      &tree,
+      &prev_tree,
+      &mut self.root,
+      &changes,
+      self.handle_event.to_owned(),
+    );
+    drop(prev_tree);
+    if let Some(trace) = trace {
+      trace.changes = changes_to_cirru(&changes);
+      trace.view_time += resolved - started;
+      trace.diff_time = diffed - resolved;
+      trace.patch_time = now() - diffed;
+    }
+    let rebuild = match patched {
+      Ok(()) => false,
+      Err(e) if e.is_dom_inconsistency() => {
+        util::warn_log!("DOM is inconsistent, rebuilding whole tree: {}", e);
+        true
+      }
+      Err(e) => return Err(e),
+    };
+    let old_tree = self.prev_tree.replace(tree);
+    self.memos = memos;
+    if rebuild {
+      self.rebuild(&old_tree)?;
+    }
+    Ok(())
+  }
+
+  /// builds DOM of the tree again to replace the rendered root, `BeforeUnmount` effects of `old_tree` are called before that,
+  /// and `Mounted` effects after that
+  fn rebuild(&mut self, old_tree: &RespoNode<T>) -> Result<(), RespoError> {
+    unmount_for_rebuild(&self.backend, old_tree, &mut self.root, &[], &[]);
+    let root = build_dom_tree(&self.backend, &self.prev_tree.borrow(), &[], self.handle_event.to_owned())?;
+    // the old root might be removed by other scripts
+    if let Some(parent) = self.backend.parent_of(&self.root.node) {
+      self.backend.remove_child(&parent, &self.root.node)?;
+    }
+    self.backend.append_child(&self.mount_target, &root.node)?;
+    self.root = root;
+    self.run_mounted_effects()
+  }
+
+  /// runs `BeforeUnmount` effects inside-out, then removes rendered elements from `mount_target`
+  pub fn unmount(mut self) -> Result<(), RespoError> {
+    let tree = self.prev_tree.borrow().to_owned();
+    let mut changes: Vec<DomChange<T>> = vec![];
+    collect_effects_inside_out_as(&tree, &[], &[], RespoEffectType::BeforeUnmount, &mut changes)?;
+    patch_tree(&self.backend, &tree, &tree, &mut self.root, &changes, self.handle_event.to_owned())?;
+    self.backend.remove_child(&self.mount_target, &self.root.node)
+  }
+
+  pub fn backend(&self) -> &B {
+    &self.backend
+  }
+
+  pub fn mount_target(&self) -> &B::Node {
+    &self.mount_target
+  }
+
+  /// the tree that was rendered last time
+  pub fn tree(&self) -> Ref<'_, RespoNode<T>> {
+    self.prev_tree.borrow()
+  }
+}
+
+/// events are handled by listeners found in the latest tree, rendering is requested by dispatching
+fn route_events<T>(prev_tree: Rc<RefCell<RespoNode<T>>>, dispatch: DispatchFn<T>) -> RespoEventMarkFn
+where
+  T: Debug + Clone + 'static,
+{
+  RespoEventMarkFn::new(move |mark: RespoEventMark| -> Result<(), RespoError> {
+    match request_for_target_handler(&prev_tree.borrow(), &mark.name, &mark.coord) {
+      Ok(handler) => {
+        if let Err(e) = handler.run(mark.event_info, dispatch.to_owned()) {
+          util::error_log!("event handler error: {:?}", e);
+        }
+      }
+      Err(msg) => {
+        util::error_log!("event not handled: {}", msg);
+      }
+    }
+
+    Ok(())
+  })
 }
 
-pub(crate) fn load_coord_target_tree<T>(tree: &RespoNode<T>, coord: &[RespoCoord]) -> Result<RespoNode<T>, String>
+pub(crate) fn load_coord_target_tree<T>(tree: &RespoNode<T>, coord: &[RespoCoord]) -> Result<RespoNode<T>, RespoError>
 where
   T: Debug + Clone,
 {
@@ -160,74 +380,86 @@ where
       _ => Ok(tree.to_owned()),
     }
   } else {
-    let branch = coord.first().ok_or("to get first branch of coord")?;
+    let branch = coord
+      .first()
+      .ok_or_else(|| RespoError::CoordMismatch(String::from("to get first branch of coord")))?;
     match (tree, branch) {
       (RespoNode::Component(RespoComponent { name, tree, .. }), RespoCoord::Comp(target_name)) => {
         if name == target_name {
           load_coord_target_tree(tree, &coord[1..])
         } else {
-          Err(format!(
-            "Mismatch in expected component name: expected {}, found {}",
+          Err(RespoError::CoordMismatch(format!(
+            "expected component {}, found {}",
             &target_name, &name
-          ))
+          )))
         }
       }
       (RespoNode::Element(RespoElement { children, .. }), RespoCoord::Key(idx)) => match children.iter().position(|(k, _)| idx == k) {
         Some(i) => {
-          let child = &children.get(i).ok_or_else(|| format!("to get child {:?} {}", idx, i))?.1;
+          let child = &children
+            .get(i)
+            .ok_or_else(|| RespoError::CoordMismatch(format!("to get child {:?} {}", idx, i)))?
+            .1;
           load_coord_target_tree(child, &coord[1..])
         }
-        None => Err(format!("no child at index key {:?}", idx)),
+        None => Err(RespoError::CoordMismatch(format!("no child at index key {:?}", idx))),
       },
       // match children.get(*idx as usize) {
       //   Some((_k, child)) => load_coord_target_tree(child, &coord[1..]),
       //   None => Err(format!("no child at index key {:?}", idx)),
       // },
-      (RespoNode::Component(..), RespoCoord::Key(..)) => {
-        Err(String::from("Type mismatch: expected a DOM element, but found a component"))
-      }
-      (RespoNode::Element { .. }, RespoCoord::Comp(..)) => {
-        Err(format!("expected component at {:?}, found target being an element", coord))
-      }
+      (RespoNode::Component(..), RespoCoord::Key(..)) => Err(RespoError::CoordMismatch(String::from(
+        "expected a DOM element, but found a component",
+      ))),
+      (RespoNode::Element { .. }, RespoCoord::Comp(..)) => Err(RespoError::CoordMismatch(format!(
+        "expected component at {:?}, found target being an element",
+        coord
+      ))),
       (RespoNode::Referenced(cell), _) => load_coord_target_tree(cell, coord),
+      (RespoNode::Memo(memo), _) => load_coord_target_tree(&memo.to_component()?, coord),
     }
   }
 }
 
-fn request_for_target_handler<T>(tree: &RespoNode<T>, event_name: &str, coord: &[RespoCoord]) -> Result<RespoListenerFn<T>, String>
+fn request_for_target_handler<T>(tree: &RespoNode<T>, event_name: &str, coord: &[RespoCoord]) -> Result<RespoListenerFn<T>, RespoError>
 where
   T: Debug + Clone,
 {
   let target_node = load_coord_target_tree(tree, coord)?;
 
   match target_node {
-    RespoNode::Component(RespoComponent { name, .. }) => Err(format!("expected element, found target being a component: {}", &name)),
+    RespoNode::Component(RespoComponent { name, .. }) => Err(RespoError::CoordMismatch(format!(
+      "expected element, found target being a component: {}",
+      &name
+    ))),
     RespoNode::Element(RespoElement { name: tag_name, event, .. }) => match event.get(event_name) {
       Some(v) => Ok((*v).to_owned()),
-      None => Err(format!(
-        "No event handler found for event '{}' on element '{}', available events: {:?}",
-        &event_name,
-        tag_name,
-        event.keys()
-      )),
+      None => Err(RespoError::MissingHandler {
+        event: event_name.to_owned(),
+        element: tag_name.to_string(),
+      }),
     },
     RespoNode::Referenced(cell) => request_for_target_handler(&cell, event_name, coord),
+    RespoNode::Memo(memo) => request_for_target_handler(&memo.to_component()?, event_name, coord),
   }
 }
 
 /// creates a DOM tree from virtual DOM with proxied event handler attached
-pub(crate) fn build_dom_tree<T>(tree: &RespoNode<T>, coord: &[RespoCoord], handle_event: RespoEventMarkFn) -> Result<Node, JsValue>
+pub(crate) fn build_dom_tree<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  coord: &[RespoCoord],
+  handle_event: RespoEventMarkFn,
+) -> Result<LiveNode<B::Node>, RespoError>
 where
   T: Debug + Clone,
+  B: DomBackend,
 {
-  let window = web_sys::window().expect("no global `window` exists");
-  let document = window.document().expect("should have a document on window");
-
   match tree {
     RespoNode::Component(RespoComponent { name, tree: child, .. }) => {
       let mut next_coord: Vec<RespoCoord> = coord.to_owned();
       next_coord.push(RespoCoord::Comp(name.to_owned()));
-      build_dom_tree(child, &next_coord, handle_event)
+      build_dom_tree(backend, child, &next_coord, handle_event)
     }
     RespoNode::Element(RespoElement {
       name,
@@ -236,36 +468,22 @@ where
       event,
       children,
     }) => {
-      let element = document.create_element(name)?;
+      let element = backend.create_element(name)?;
+      let mut live_children = Vec::with_capacity(children.len());
       let mut inner_set = false;
       for (key, value) in attrs {
         let key = key.as_ref();
         match key {
-          "style" => warn_1(&"style is handled outside attrs".into()),
-          "innerText" => {
+          "style" => warn_log!("style is handled outside attrs"),
+          "innerText" | "innerHTML" => {
             inner_set = true;
-            element.dyn_ref::<HtmlElement>().expect("into html element").set_inner_text(value)
-          }
-          "innerHTML" => {
-            inner_set = true;
-            element.set_inner_html(value)
-          }
-          "htmlFor" => element
-            .dyn_ref::<HtmlLabelElement>()
-            .expect("into label element")
-            .set_html_for(value),
-          "value" if &**name == "textarea" => element
-            .dyn_ref::<HtmlTextAreaElement>()
-            .expect("into textarea element")
-            .set_value(value),
-          "value" if &**name == "input" => element.dyn_ref::<HtmlInputElement>().expect("into input element").set_value(value),
-          _ => {
-            element.set_attribute(key, value)?;
+            backend.set_attribute(&element, key, value)?;
           }
+          _ => backend.set_attribute(&element, key, value)?,
         }
       }
-      if !style.is_empty() {
-        element.set_attribute("style", &style.to_string())?;
+      for (property, value) in &style.0 {
+        backend.set_style(&element, property, value)?;
       }
       if inner_set && !children.is_empty() {
         warn_log!(
@@ -278,19 +496,255 @@ where
         let mut next_coord = coord.to_owned();
         next_coord.push(RespoCoord::Key(k.to_owned()));
         let handler = handle_event.to_owned();
-        element.append_child(&build_dom_tree(child, &next_coord, handler)?)?;
+        let live_child = build_dom_tree(backend, child, &next_coord, handler)?;
+        backend.append_child(&element, &live_child.node)?;
+        live_children.push(live_child);
       }
 
       // util::log!("create handler for element: {} {:?}", name, event);
 
-      for key in event.keys() {
-        let coord = coord.to_owned();
-        let handler = handle_event.to_owned();
-        attach_event(&element, key.as_ref(), &coord, handler)?;
+      for (key, listener) in event {
+        backend.attach_listener(&element, key, listener.options, event_callback(key, coord, handle_event.to_owned()))?;
+      }
+
+      Ok(LiveNode {
+        node: element,
+        children: live_children,
+      })
+    }
+    RespoNode::Referenced(cell) => build_dom_tree(backend, cell, coord, handle_event),
+    RespoNode::Memo(memo) => build_dom_tree(backend, &memo.to_component()?, coord, handle_event),
+  }
+}
+
+/// wraps events from element at `coord` into marks for `handle_event`
+pub(crate) fn event_callback(name: &str, coord: &[RespoCoord], handle_event: RespoEventMarkFn) -> RespoEventCallback {
+  let name = name.to_owned();
+  let coord = coord.to_owned();
+  Rc::new(move |event| handle_event.run(RespoEventMark::new(&name, &coord, event)))
+}
+
+/// reuses DOM rendered by server under mount target, whitespaces around the root element are dropped
+fn hydrate_mount_target<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  mount_target: &B::Node,
+  handle_event: RespoEventMarkFn,
+  mismatches: &mut Vec<String>,
+) -> Result<LiveNode<B::Node>, RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  for idx in (0..backend.child_count(mount_target)).rev() {
+    if let Some(child) = backend.child_at(mount_target, idx) {
+      if backend.tag_name(&child).is_none() && backend.get_attribute(&child, "innerText").unwrap_or_default().trim().is_empty() {
+        backend.remove_child(mount_target, &child)?;
+      }
+    }
+  }
+
+  match backend.child_count(mount_target) {
+    1 => {
+      let root = backend
+        .child_at(mount_target, 0)
+        .ok_or_else(|| RespoError::Dom(String::from("to get root element")))?;
+      hydrate_dom_tree(backend, tree, &[], &root, &[], handle_event, mismatches)
+    }
+    n => {
+      mismatches.push(format!("expected a single node under mount target, found {}, rebuilding", n));
+      while let Some(child) = backend.child_at(mount_target, 0) {
+        backend.remove_child(mount_target, &child)?;
+      }
+      let element = build_dom_tree(backend, tree, &[], handle_event)?;
+      backend.append_child(mount_target, &element.node)?;
+      Ok(element)
+    }
+  }
+}
+
+/// builds element from virtual tree in place of `node`
+fn replace_dom_tree<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  coord: &[RespoCoord],
+  node: &B::Node,
+  handle_event: RespoEventMarkFn,
+) -> Result<LiveNode<B::Node>, RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  let parent = backend
+    .parent_of(node)
+    .ok_or_else(|| RespoError::Dom(String::from("to get parent node")))?;
+  let new_element = build_dom_tree(backend, tree, coord, handle_event)?;
+  backend.insert_before(&parent, &new_element.node, node)?;
+  backend.remove_child(&parent, node)?;
+  Ok(new_element)
+}
+
+/// attribute name as HTML reports it, names are case-insensitive in HTML
+fn html_attribute_name(key: &str) -> String {
+  if key == "htmlFor" {
+    String::from("for")
+  } else {
+    key.to_ascii_lowercase()
+  }
+}
+
+/// `render_to_html` writes line breaks of `innerText` as `<br>`, which text content of parsed HTML does not include,
+/// so texts are compared without line breaks
+fn text_without_line_breaks(text: &str) -> String {
+  text.chars().filter(|c| *c != '\n' && *c != '\r').collect()
+}
+
+/// walks existing DOM along with virtual tree, attaching event listeners like `build_dom_tree`.
+/// mismatched elements are rebuilt from virtual tree, mismatched attributes and contents are overwritten.
+fn hydrate_dom_tree<T, B>(
+  backend: &B,
+  tree: &RespoNode<T>,
+  coord: &[RespoCoord],
+  node: &B::Node,
+  dom_path: &[u32],
+  handle_event: RespoEventMarkFn,
+  mismatches: &mut Vec<String>,
+) -> Result<LiveNode<B::Node>, RespoError>
+where
+  T: Debug + Clone,
+  B: DomBackend,
+{
+  match tree {
+    RespoNode::Component(RespoComponent { name, tree: child, .. }) => {
+      let mut next_coord: Vec<RespoCoord> = coord.to_owned();
+      next_coord.push(RespoCoord::Comp(name.to_owned()));
+      hydrate_dom_tree(backend, child, &next_coord, node, dom_path, handle_event, mismatches)
+    }
+    RespoNode::Element(RespoElement {
+      name,
+      attributes: attrs,
+      style,
+      event,
+      children,
+    }) => {
+      match backend.tag_name(node) {
+        Some(tag) if tag.eq_ignore_ascii_case(name) => {}
+        found => {
+          mismatches.push(format!("expected <{}> at {:?}, found {:?}, rebuilding", name, dom_path, found));
+          return replace_dom_tree(backend, tree, coord, node, handle_event);
+        }
+      }
+
+      // names in virtual DOM may be in camel case, while DOM reports them in lowercase
+      let dom_names: HashMap<String, String> = backend
+        .attribute_names(node)
+        .into_iter()
+        .map(|dom_key| (dom_key.to_ascii_lowercase(), dom_key))
+        .collect();
+      let mut live = LiveNode::new(node.to_owned());
+      let mut inner_set = false;
+      for (key, value) in attrs {
+        let key = key.as_ref();
+        match key {
+          "style" => {}
+          "innerText" => {
+            inner_set = true;
+            let found = backend.get_attribute(node, key).unwrap_or_default();
+            if text_without_line_breaks(&found) != text_without_line_breaks(value) {
+              mismatches.push(format!("{} mismatched at {:?}", key, dom_path));
+              backend.set_attribute(node, key, value)?;
+            }
+          }
+          "innerHTML" => {
+            inner_set = true;
+            if backend.get_attribute(node, key).as_ref() != Some(value) {
+              mismatches.push(format!("{} mismatched at {:?}", key, dom_path));
+              backend.set_attribute(node, key, value)?;
+            }
+          }
+          // values might be edited by users before hydrating, they are not mismatches
+          "value" if &**name == "textarea" || &**name == "input" => {
+            if &**name == "textarea" {
+              inner_set = true;
+            }
+            if backend.get_attribute(node, key).as_ref() != Some(value) {
+              backend.set_attribute(node, key, value)?;
+            }
+          }
+          _ => {
+            let dom_key = dom_names.get(&html_attribute_name(key));
+            if dom_key.and_then(|k| backend.get_attribute(node, k)).as_ref() != Some(value) {
+              mismatches.push(format!("attribute {} mismatched at {:?}", key, dom_path));
+              if let Some(dom_key) = dom_key {
+                backend.remove_attribute(node, dom_key)?;
+              }
+              backend.set_attribute(node, key, value)?;
+            }
+          }
+        }
+      }
+      let expected_names: HashSet<String> = attrs.keys().map(|k| html_attribute_name(k)).collect();
+      for (name_in_lowercase, dom_key) in &dom_names {
+        if name_in_lowercase != "style" && name_in_lowercase != "value" && !expected_names.contains(name_in_lowercase) {
+          mismatches.push(format!("unexpected attribute {} at {:?}", dom_key, dom_path));
+          backend.remove_attribute(node, dom_key)?;
+        }
+      }
+
+      let found_style: HashMap<String, String> = backend.styles(node).into_iter().collect();
+      let expected_style: HashMap<String, String> = style.0.iter().map(|(k, v)| (k.to_string(), v.to_owned())).collect();
+      if found_style != expected_style {
+        mismatches.push(format!("style mismatched at {:?}", dom_path));
+        for property in found_style.keys() {
+          if !expected_style.contains_key(property) {
+            backend.remove_style(node, property)?;
+          }
+        }
+        for (property, value) in &expected_style {
+          if found_style.get(property) != Some(value) {
+            backend.set_style(node, property, value)?;
+          }
+        }
+      }
+
+      if !inner_set {
+        let child_count = backend.child_count(node);
+        if child_count as usize != children.len() {
+          mismatches.push(format!(
+            "expected {} children at {:?}, found {}, rebuilding",
+            children.len(),
+            dom_path,
+            child_count
+          ));
+          return replace_dom_tree(backend, tree, coord, node, handle_event);
+        }
+        for (idx, (k, child)) in children.iter().enumerate() {
+          let child_node = backend
+            .child_at(node, idx as u32)
+            .ok_or_else(|| RespoError::Dom(String::from("to get child node")))?;
+          let mut next_coord = coord.to_owned();
+          next_coord.push(RespoCoord::Key(k.to_owned()));
+          let mut next_dom_path = dom_path.to_owned();
+          next_dom_path.push(idx as u32);
+          live.children.push(hydrate_dom_tree(
+            backend,
+            child,
+            &next_coord,
+            &child_node,
+            &next_dom_path,
+            handle_event.to_owned(),
+            mismatches,
+          )?);
+        }
+      }
+
+      for (key, listener) in event {
+        backend.attach_listener(node, key, listener.options, event_callback(key, coord, handle_event.to_owned()))?;
       }
 
-      Ok(element.dyn_ref::<Node>().expect("converting to Node").to_owned())
+      Ok(live)
     }
-    RespoNode::Referenced(cell) => build_dom_tree(cell, coord, handle_event),
+    RespoNode::Referenced(cell) => hydrate_dom_tree(backend, cell, coord, node, dom_path, handle_event, mismatches),
+    RespoNode::Memo(memo) => hydrate_dom_tree(backend, &memo.to_component()?, coord, node, dom_path, handle_event, mismatches),
   }
 }
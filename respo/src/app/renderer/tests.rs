@@ -0,0 +1,99 @@
+//! tests on hydrating DOM rendered by server, with DOM kept in `MemoryBackend`
+
+use crate::app::backend::{DomBackend, MemoryBackend, MemoryNode};
+use crate::css::respo_style;
+use crate::node::{DispatchFn, RespoNode};
+use crate::{div, span};
+
+use super::RespoRenderer;
+
+fn view() -> RespoNode<()> {
+  div()
+    .attr("contentEditable", "true")
+    .attr("title", "t")
+    .style(respo_style().insert("color", "red".to_owned()))
+    .on_click(|_, _| Ok(()))
+    .children([span().inner_text("fresh").to_node()])
+    .to_node()
+}
+
+/// DOM like a browser parses from server HTML, attribute names are in lowercase
+fn server_dom(backend: &MemoryBackend, text: &str) -> MemoryNode {
+  let mount_target = backend.create_element("div").expect("create mount target");
+  let root = backend.create_element("div").expect("create root");
+  backend.set_attribute(&root, "contenteditable", "true").expect("set attribute");
+  backend.set_attribute(&root, "title", "t").expect("set attribute");
+  backend.set_style(&root, "color", "red").expect("set style");
+  let child = backend.create_element("span").expect("create child");
+  backend.set_attribute(&child, "innerText", text).expect("set text");
+  backend.append_child(&root, &child).expect("append child");
+  backend.append_child(&mount_target, &root).expect("append root");
+  mount_target
+}
+
+#[test]
+fn hydrate_reuses_matched_dom() {
+  let backend = MemoryBackend::new();
+  let mount_target = server_dom(&backend, "fresh");
+  let root = backend.child_at(&mount_target, 0).expect("root");
+
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let (_renderer, mismatches) = RespoRenderer::hydrate(backend.to_owned(), mount_target.to_owned(), view(), dispatch).expect("hydrate");
+
+  assert_eq!(mismatches, Vec::<String>::new(), "camel case names match lowercase names in DOM");
+  let hydrated = backend.child_at(&mount_target, 0).expect("hydrated root");
+  assert!(hydrated.is_same_node(&root));
+  assert_eq!(hydrated.listeners(), vec!["click".to_owned()]);
+}
+
+#[test]
+fn hydrate_fixes_mismatched_contents_and_attributes() {
+  let backend = MemoryBackend::new();
+  let mount_target = server_dom(&backend, "stale");
+  let root = backend.child_at(&mount_target, 0).expect("root");
+  backend.set_attribute(&root, "data-extra", "x").expect("set attribute");
+  backend.set_style(&root, "margin", "1px").expect("set style");
+
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let (_renderer, mut mismatches) =
+    RespoRenderer::hydrate(backend.to_owned(), mount_target.to_owned(), view(), dispatch).expect("hydrate");
+
+  mismatches.sort();
+  assert_eq!(
+    mismatches,
+    vec![
+      "innerText mismatched at [0]".to_owned(),
+      "style mismatched at []".to_owned(),
+      "unexpected attribute data-extra at []".to_owned(),
+    ]
+  );
+  assert!(backend.child_at(&mount_target, 0).expect("hydrated root").is_same_node(&root));
+  assert_eq!(root.attribute("data-extra"), None);
+  assert_eq!(root.attribute("contenteditable"), Some("true".to_owned()));
+  assert_eq!(root.style("margin"), None);
+  assert_eq!(root.children()[0].attribute("innerText"), Some("fresh".to_owned()));
+}
+
+#[test]
+fn hydrate_matches_multiline_text() {
+  let backend = MemoryBackend::new();
+  // text content of `<span>line 1<br>line 2</span>` leaves out the line break
+  let mount_target = server_dom(&backend, "line 1line 2");
+  let child = backend.child_at(&mount_target, 0).expect("root").children()[0].to_owned();
+
+  let tree = div()
+    .attr("contentEditable", "true")
+    .attr("title", "t")
+    .style(respo_style().insert("color", "red".to_owned()))
+    .children([span().inner_text("line 1\nline 2").to_node()])
+    .to_node();
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let (_renderer, mismatches) = RespoRenderer::hydrate(backend.to_owned(), mount_target.to_owned(), tree, dispatch).expect("hydrate");
+
+  assert_eq!(mismatches, Vec::<String>::new());
+  assert_eq!(
+    child.attribute("innerText"),
+    Some("line 1line 2".to_owned()),
+    "text is not rewritten"
+  );
+}
@@ -0,0 +1,192 @@
+use std::{
+  cell::RefCell,
+  fmt::Debug,
+  rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::app::util;
+use crate::RespoError;
+
+/// callback that renders the latest store into the DOM
+pub(crate) type RenderFn = Box<dyn FnMut() -> Result<(), RespoError>>;
+
+/// requests rendering on demand instead of polling in a loop.
+///
+/// `schedule` requests a single `requestAnimationFrame`, calls in the same frame are coalesced,
+/// and nothing runs when there's no call. With `throttle`, renders are at least that many milliseconds apart.
+#[derive(Clone)]
+pub(crate) struct RenderScheduler {
+  state: Rc<RefCell<SchedulerState>>,
+}
+
+struct SchedulerState {
+  /// id of requested animation frame that has not run yet
+  frame_id: Option<i32>,
+  /// id of timeout waiting for throttling, a frame is requested after it
+  timeout_id: Option<i32>,
+  stopped: bool,
+  throttle: Option<i32>,
+  /// time of last render, from `Date.now()`
+  last_render: f64,
+  render: Option<RenderFn>,
+  on_frame: Option<Closure<dyn FnMut()>>,
+  on_timeout: Option<Closure<dyn FnMut()>>,
+}
+
+impl SchedulerState {
+  fn is_pending(&self) -> bool {
+    self.frame_id.is_some() || self.timeout_id.is_some()
+  }
+
+  fn request_frame(&mut self) {
+    if let Some(f) = &self.on_frame {
+      self.frame_id = request_animation_frame(f);
+    }
+  }
+}
+
+impl Debug for RenderScheduler {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let state = self.state.borrow();
+    f.debug_struct("RenderScheduler")
+      .field("pending", &state.is_pending())
+      .field("stopped", &state.stopped)
+      .field("throttle", &state.throttle)
+      .finish()
+  }
+}
+
+impl RenderScheduler {
+  pub fn new(throttle: Option<i32>) -> Self {
+    let state = Rc::new(RefCell::new(SchedulerState {
+      frame_id: None,
+      timeout_id: None,
+      stopped: false,
+      throttle,
+      last_render: 0.0,
+      render: None,
+      on_frame: None,
+      on_timeout: None,
+    }));
+
+    // closures hold weak references, so they do not keep the scheduler alive
+    let on_frame = Closure::wrap(Box::new({
+      let state = Rc::downgrade(&state);
+      move || run_frame(&state)
+    }) as Box<dyn FnMut()>);
+    let on_timeout = Closure::wrap(Box::new({
+      let state = Rc::downgrade(&state);
+      move || {
+        if let Some(state) = state.upgrade() {
+          let mut s = state.borrow_mut();
+          s.timeout_id = None;
+          s.request_frame();
+        }
+      }
+    }) as Box<dyn FnMut()>);
+    {
+      let mut s = state.borrow_mut();
+      s.on_frame = Some(on_frame);
+      s.on_timeout = Some(on_timeout);
+    }
+
+    Self { state }
+  }
+
+  /// sets the function called in frames
+  pub fn set_render(&self, render: RenderFn) {
+    self.state.borrow_mut().render = Some(render);
+  }
+
+  /// requests a render in next frame, does nothing when a render is already pending or the scheduler is stopped
+  pub fn schedule(&self) {
+    let mut state = self.state.borrow_mut();
+    if state.stopped || state.is_pending() {
+      return;
+    }
+
+    let wait = match state.throttle {
+      Some(throttle) => throttle as f64 - (js_sys::Date::now() - state.last_render),
+      None => 0.0,
+    };
+    if wait > 0.0 {
+      if let Some(f) = &state.on_timeout {
+        state.timeout_id = set_timeout(f, wait.ceil() as i32);
+      }
+    } else {
+      state.request_frame();
+    }
+  }
+
+  /// cancels pending render and drops the render function, later calls of `schedule` are ignored
+  pub fn stop(&self) {
+    let render = {
+      let mut state = self.state.borrow_mut();
+      state.stopped = true;
+      if let Some(window) = web_sys::window() {
+        if let Some(id) = state.frame_id.take() {
+          let _ = window.cancel_animation_frame(id);
+        }
+        if let Some(id) = state.timeout_id.take() {
+          window.clear_timeout_with_handle(id);
+        }
+      }
+      state.render.take()
+    };
+    // dropped after releasing the state, since it may hold things that schedule again when dropped
+    drop(render);
+  }
+
+  pub fn is_stopped(&self) -> bool {
+    self.state.borrow().stopped
+  }
+}
+
+/// renders once, render function is taken out during the call since rendering may dispatch and schedule again
+fn run_frame(state: &Weak<RefCell<SchedulerState>>) {
+  let Some(state) = state.upgrade() else {
+    return;
+  };
+  let render = {
+    let mut s = state.borrow_mut();
+    s.frame_id = None;
+    s.last_render = js_sys::Date::now();
+    s.render.take()
+  };
+  if let Some(mut render) = render {
+    if let Err(e) = render() {
+      util::warn_log!("Failure in rendering, DOM might be inconsistent with states. Details: {}", e);
+    }
+    // not put back after being stopped, dropping happens without borrowing the state
+    if !state.borrow().stopped {
+      let mut s = state.borrow_mut();
+      if s.render.is_none() {
+        s.render = Some(render);
+      }
+    }
+  }
+}
+
+fn request_animation_frame(f: &Closure<dyn FnMut()>) -> Option<i32> {
+  let window = web_sys::window()?;
+  match window.request_animation_frame(f.as_ref().unchecked_ref()) {
+    Ok(id) => Some(id),
+    Err(e) => {
+      util::error_log!("failed to request animation frame: {:?}", e);
+      None
+    }
+  }
+}
+
+fn set_timeout(f: &Closure<dyn FnMut()>, delay: i32) -> Option<i32> {
+  let window = web_sys::window()?;
+  match window.set_timeout_with_callback_and_timeout_and_arguments_0(f.as_ref().unchecked_ref(), delay) {
+    Ok(id) => Some(id),
+    Err(e) => {
+      util::error_log!("failed to set timeout: {:?}", e);
+      None
+    }
+  }
+}
@@ -0,0 +1,170 @@
+//! persistence of stores, abstracted so apps are not bound to local storage.
+//!
+//! - `LocalStorage` and `SessionStorage` use Web Storage APIs, local storage is the default of `RespoApp::get_storage`.
+//! - `IndexedDbStorage` saves into IndexedDB, which has larger quota, operations finish asynchronously.
+//! - `MemoryStorage` keeps content in memory, for running natively, like in `cargo test`.
+//!
+//! Content is saved in a versioned envelope, older content is upgraded with migrations when loading.
+
+mod indexed_db;
+mod memory;
+#[cfg(test)]
+mod tests;
+mod web;
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::app::util;
+use crate::{RespoError, RespoStore};
+
+pub use indexed_db::IndexedDbStorage;
+pub use memory::MemoryStorage;
+pub use web::{LocalStorage, SessionStorage};
+
+/// receives content loaded from a storage, `None` when nothing is saved under the key
+pub type StorageLoadFn = Box<dyn FnOnce(Result<Option<String>, RespoError>)>;
+
+/// a place to save stores of apps into, see `RespoApp::get_storage`
+pub trait RespoStorage {
+  /// reads content saved under `key`, synchronous storages call `done` before returning
+  fn load(&self, key: &str, done: StorageLoadFn);
+  /// saves content under `key`, asynchronous storages log errors that happen later
+  fn save(&self, key: &str, content: &str) -> Result<(), RespoError>;
+  fn remove(&self, key: &str) -> Result<(), RespoError>;
+}
+
+/// upgrades content saved from `RespoStore::to_string` by one version, see `RespoApp::storage_migrations`
+pub type StorageMigration = fn(String) -> Result<String, RespoError>;
+
+/// content saved before envelopes were added is treated as version `0`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StorageEnvelope {
+  respo_version: usize,
+  content: String,
+}
+
+/// wraps content of a store with the version it's saved in
+pub(crate) fn wrap_storage(version: usize, content: String) -> String {
+  let envelope = StorageEnvelope {
+    respo_version: version,
+    content,
+  };
+  serde_json::to_string(&envelope).unwrap_or_else(|e| {
+    util::warn_log!("failed to wrap storage: {}", e);
+    envelope.content
+  })
+}
+
+/// takes content out of the envelope and runs migrations from its version to the latest one,
+/// the migration at index `n` upgrades content from version `n` to `n + 1`
+pub(crate) fn unwrap_storage(saved: &str, migrations: &[StorageMigration]) -> Result<String, RespoError> {
+  let (version, mut content) = match serde_json::from_str::<StorageEnvelope>(saved) {
+    Ok(envelope) => (envelope.respo_version, envelope.content),
+    Err(_) => (0, saved.to_owned()),
+  };
+  if version > migrations.len() {
+    return Err(RespoError::StateRestore(format!(
+      "storage is saved in version {}, newer than version {} of the app",
+      version,
+      migrations.len()
+    )));
+  }
+  for (idx, migrate) in migrations.iter().enumerate().skip(version) {
+    content = migrate(content).map_err(|e| RespoError::StateRestore(format!("migration from version {}: {}", idx, e)))?;
+  }
+  Ok(content)
+}
+
+/// loads content under `key` into `store`, see `RespoApp::load_storage`
+pub(crate) fn load_store<S, F>(
+  storage: Rc<dyn RespoStorage>,
+  key: &str,
+  migrations: Vec<StorageMigration>,
+  store: Rc<RefCell<S>>,
+  done: F,
+) where
+  S: RespoStore + 'static,
+  F: FnOnce(Result<(), RespoError>) + 'static,
+{
+  // broken content is moved here for recovering by hand, since it's overwritten in next backup
+  let failed_key = format!("{}:failed", key);
+  storage.to_owned().load(
+    key,
+    Box::new(move |loaded| {
+      let ret = match loaded {
+        Ok(Some(s)) => match unwrap_storage(&s, &migrations).and_then(|c| S::try_from_string(&c)) {
+          Ok(s) => {
+            *store.borrow_mut() = s;
+            Ok(())
+          }
+          Err(e) => {
+            util::error_log!("error: {}, saved content is moved to {}", e, failed_key);
+            if let Err(save_error) = storage.save(&failed_key, &s) {
+              util::error_log!("failed to move saved content to {}: {}", failed_key, save_error);
+            }
+            Err(e)
+          }
+        },
+        Ok(None) => {
+          util::log!("no storage");
+          Ok(())
+        }
+        Err(e) => Err(e),
+      };
+      done(ret)
+    }),
+  );
+}
+
+/// saves `store` under `key`, in the envelope of `version`
+pub(crate) fn backup_store<S>(storage: &dyn RespoStorage, key: &str, version: usize, store: &Rc<RefCell<S>>) -> Result<(), RespoError>
+where
+  S: RespoStore,
+{
+  let content = wrap_storage(version, store.borrow().to_string());
+  storage.save(key, &content)
+}
+
+/// saves store after dispatching stops for a while, each `schedule` call restarts the timer
+pub(crate) struct BackupScheduler {
+  delay: i32,
+  timeout_id: Rc<RefCell<Option<i32>>>,
+  on_timeout: Closure<dyn FnMut()>,
+}
+
+impl BackupScheduler {
+  pub fn new(delay: i32, save: Box<dyn Fn()>) -> Self {
+    let timeout_id = Rc::new(RefCell::new(None));
+    let on_timeout = Closure::wrap(Box::new({
+      let timeout_id = timeout_id.to_owned();
+      move || {
+        timeout_id.borrow_mut().take();
+        save();
+      }
+    }) as Box<dyn FnMut()>);
+    Self {
+      delay,
+      timeout_id,
+      on_timeout,
+    }
+  }
+
+  pub fn schedule(&self) {
+    let Some(window) = web_sys::window() else {
+      return;
+    };
+    if let Some(id) = self.timeout_id.borrow_mut().take() {
+      window.clear_timeout_with_handle(id);
+    }
+    match window.set_timeout_with_callback_and_timeout_and_arguments_0(self.on_timeout.as_ref().unchecked_ref(), self.delay) {
+      Ok(id) => {
+        *self.timeout_id.borrow_mut() = Some(id);
+      }
+      Err(e) => util::error_log!("failed to schedule backup: {:?}", e),
+    }
+  }
+}
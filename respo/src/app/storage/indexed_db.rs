@@ -0,0 +1,250 @@
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Event, IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+use crate::app::util;
+use crate::RespoError;
+
+use super::{RespoStorage, StorageLoadFn};
+
+type DatabaseFn = Box<dyn FnOnce(Result<IdbDatabase, RespoError>)>;
+
+/// saves content into an object store of IndexedDB, which has a much larger quota than local storage.
+///
+/// Operations finish asynchronously, so saving in `beforeunload` may not complete,
+/// prefer `RespoApp::backup_model_on_hidden` or `RespoApp::get_backup_delay` with it.
+/// The database is opened on first use and kept for later operations.
+#[derive(Debug, Clone)]
+pub struct IndexedDbStorage {
+  db_name: String,
+  store_name: String,
+  connection: Rc<RefCell<Connection>>,
+}
+
+/// connection to the database, shared by clones of a storage
+enum Connection {
+  Closed,
+  /// operations waiting for the database being opened
+  Opening(Vec<DatabaseFn>),
+  /// the listener closes the database when another connection upgrades it
+  Open {
+    db: IdbDatabase,
+    _on_version_change: Closure<dyn FnMut(Event)>,
+  },
+}
+
+impl Debug for Connection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Closed => write!(f, "Closed"),
+      Self::Opening(waiting) => write!(f, "Opening({} waiting)", waiting.len()),
+      Self::Open { db, .. } => write!(f, "Open({})", db.name()),
+    }
+  }
+}
+
+impl IndexedDbStorage {
+  /// content is saved into object store `store_name` of database `db_name`, both are created when missing
+  pub fn new(db_name: &str, store_name: &str) -> Self {
+    Self {
+      db_name: db_name.to_owned(),
+      store_name: store_name.to_owned(),
+      connection: Rc::new(RefCell::new(Connection::Closed)),
+    }
+  }
+
+  /// calls `f` with the opened database, operations during opening wait for the same request
+  fn with_db(&self, f: DatabaseFn) {
+    let mut connection = self.connection.borrow_mut();
+    match &mut *connection {
+      Connection::Open { db, .. } => {
+        let db = db.to_owned();
+        drop(connection);
+        f(Ok(db))
+      }
+      Connection::Opening(waiting) => waiting.push(f),
+      Connection::Closed => {
+        *connection = Connection::Opening(vec![f]);
+        drop(connection);
+        self.open(None)
+      }
+    }
+  }
+
+  /// opens the database in `version`, or in its current version when it's `None`.
+  /// the object store is created in upgrading, so when the database exists without the object store,
+  /// it's opened again in a newer version
+  fn open(&self, version: Option<u32>) {
+    let request = match open_database(&self.db_name, version) {
+      Ok(request) => request,
+      Err(e) => return self.opened(Err(e)),
+    };
+
+    let on_upgrade = Closure::<dyn FnMut(Event)>::new({
+      let request = request.to_owned();
+      let store_name = self.store_name.to_owned();
+      move |_e: Event| {
+        let created = request_result::<IdbDatabase>(&request).and_then(|db| {
+          if !db.object_store_names().contains(&store_name) {
+            db.create_object_store(&store_name)?;
+          }
+          Ok(())
+        });
+        if let Err(e) = created {
+          util::error_log!("failed to create object store: {}", e);
+        }
+      }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+    let storage = self.to_owned();
+    let open_request = request.to_owned();
+    on_request(
+      &request,
+      Box::new(move |result| {
+        // upgrading happens before success, the listener is no longer needed
+        open_request.set_onupgradeneeded(None);
+        drop(on_upgrade);
+        match result.and_then(|v| v.dyn_into::<IdbDatabase>().map_err(RespoError::from)) {
+          Ok(db) if !db.object_store_names().contains(&storage.store_name) => {
+            db.close();
+            if version.is_some() {
+              // upgraded already, creating failed and it's logged
+              storage.opened(Err(RespoError::Dom(format!("object store {} not created", storage.store_name))));
+            } else {
+              storage.open(Some(db.version() as u32 + 1));
+            }
+          }
+          opened => storage.opened(opened),
+        }
+      }),
+    );
+  }
+
+  /// keeps the database and calls operations waiting for it
+  fn opened(&self, result: Result<IdbDatabase, RespoError>) {
+    let next = match &result {
+      Ok(db) => {
+        let on_version_change = Closure::<dyn FnMut(Event)>::new({
+          let db = db.to_owned();
+          let connection = Rc::downgrade(&self.connection);
+          move |_e: Event| {
+            // another connection is upgrading the database, it's opened again on next use
+            db.close();
+            if let Some(connection) = connection.upgrade() {
+              *connection.borrow_mut() = Connection::Closed;
+            }
+          }
+        });
+        db.set_onversionchange(Some(on_version_change.as_ref().unchecked_ref()));
+        Connection::Open {
+          db: db.to_owned(),
+          _on_version_change: on_version_change,
+        }
+      }
+      Err(_) => Connection::Closed,
+    };
+    let prev = std::mem::replace(&mut *self.connection.borrow_mut(), next);
+    if let Connection::Opening(waiting) = prev {
+      for f in waiting {
+        f(result.to_owned())
+      }
+    }
+  }
+
+  /// calls `f` with the object store in a transaction of `mode`
+  fn with_store<F>(&self, mode: IdbTransactionMode, f: F)
+  where
+    F: FnOnce(Result<IdbObjectStore, RespoError>) + 'static,
+  {
+    let store_name = self.store_name.to_owned();
+    self.with_db(Box::new(move |db| {
+      f(db.and_then(|db| Ok(db.transaction_with_str_and_mode(&store_name, mode)?.object_store(&store_name)?)))
+    }));
+  }
+}
+
+fn open_database(name: &str, version: Option<u32>) -> Result<IdbOpenDbRequest, RespoError> {
+  let window = web_sys::window().ok_or_else(|| RespoError::Dom(String::from("window not found")))?;
+  let factory = window
+    .indexed_db()?
+    .ok_or_else(|| RespoError::Dom(String::from("IndexedDB is not available")))?;
+  match version {
+    Some(version) => Ok(factory.open_with_u32(name, version)?),
+    None => Ok(factory.open(name)?),
+  }
+}
+
+fn request_result<T: JsCast>(request: &IdbRequest) -> Result<T, RespoError> {
+  Ok(request.result()?.dyn_into::<T>()?)
+}
+
+/// calls `f` with result of the request once it succeeds or fails
+fn on_request(request: &IdbRequest, f: Box<dyn FnOnce(Result<JsValue, RespoError>)>) {
+  // one callback for both events, it's freed after being called, handlers are cleared so it's not called again
+  let on_done = Closure::once_into_js({
+    let request = request.to_owned();
+    move |e: Event| {
+      request.set_onsuccess(None);
+      request.set_onerror(None);
+      if e.type_() == "success" {
+        f(request.result().map_err(RespoError::from))
+      } else {
+        let message = match request.error() {
+          Ok(Some(e)) => e.message(),
+          _ => String::from("unknown error"),
+        };
+        f(Err(RespoError::Dom(format!("IndexedDB request failed: {}", message))))
+      }
+    }
+  });
+  request.set_onsuccess(Some(on_done.unchecked_ref()));
+  request.set_onerror(Some(on_done.unchecked_ref()));
+}
+
+/// logs failures of requests that nobody waits for
+fn log_failure(key: &str, operation: &'static str) -> Box<dyn FnOnce(Result<JsValue, RespoError>)> {
+  let key = key.to_owned();
+  Box::new(move |result| {
+    if let Err(e) = result {
+      util::error_log!("failed to {} {} in IndexedDB: {}", operation, key, e);
+    }
+  })
+}
+
+impl RespoStorage for IndexedDbStorage {
+  fn load(&self, key: &str, done: StorageLoadFn) {
+    let key = key.to_owned();
+    self.with_store(IdbTransactionMode::Readonly, move |store| {
+      let request = match store.and_then(|store| Ok(store.get(&JsValue::from_str(&key))?)) {
+        Ok(request) => request,
+        Err(e) => return done(Err(e)),
+      };
+      on_request(&request, Box::new(move |result| done(result.map(|v| v.as_string()))));
+    });
+  }
+
+  fn save(&self, key: &str, content: &str) -> Result<(), RespoError> {
+    let key = key.to_owned();
+    let content = content.to_owned();
+    self.with_store(IdbTransactionMode::Readwrite, move |store| {
+      match store.and_then(|store| Ok(store.put_with_key(&JsValue::from_str(&content), &JsValue::from_str(&key))?)) {
+        Ok(request) => on_request(&request, log_failure(&key, "save")),
+        Err(e) => util::error_log!("failed to save {} in IndexedDB: {}", key, e),
+      }
+    });
+    Ok(())
+  }
+
+  fn remove(&self, key: &str) -> Result<(), RespoError> {
+    let key = key.to_owned();
+    self.with_store(IdbTransactionMode::Readwrite, move |store| {
+      match store.and_then(|store| Ok(store.delete(&JsValue::from_str(&key))?)) {
+        Ok(request) => on_request(&request, log_failure(&key, "remove")),
+        Err(e) => util::error_log!("failed to remove {} in IndexedDB: {}", key, e),
+      }
+    });
+    Ok(())
+  }
+}
@@ -0,0 +1,51 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::RespoError;
+
+use super::{RespoStorage, StorageLoadFn};
+
+/// storage that keeps content in memory, no browser APIs involved.
+/// Clones share the content, keep one to check what's saved.
+///
+/// ```rust
+/// use std::{cell::RefCell, rc::Rc};
+/// use respo::{MemoryStorage, RespoStorage};
+///
+/// let storage = MemoryStorage::new();
+/// storage.save("key", "content").unwrap();
+/// let loaded = Rc::new(RefCell::new(None));
+/// storage.load("key", Box::new({
+///   let loaded = loaded.to_owned();
+///   move |c| *loaded.borrow_mut() = c.unwrap()
+/// }));
+/// assert_eq!(loaded.take(), Some("content".to_owned()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage(Rc<RefCell<HashMap<String, String>>>);
+
+impl MemoryStorage {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// content saved under `key`
+  pub fn get(&self, key: &str) -> Option<String> {
+    self.0.borrow().get(key).cloned()
+  }
+}
+
+impl RespoStorage for MemoryStorage {
+  fn load(&self, key: &str, done: StorageLoadFn) {
+    done(Ok(self.get(key)))
+  }
+
+  fn save(&self, key: &str, content: &str) -> Result<(), RespoError> {
+    self.0.borrow_mut().insert(key.to_owned(), content.to_owned());
+    Ok(())
+  }
+
+  fn remove(&self, key: &str) -> Result<(), RespoError> {
+    self.0.borrow_mut().remove(key);
+    Ok(())
+  }
+}
@@ -0,0 +1,83 @@
+//! tests on saving stores in envelopes and loading them from `MemoryStorage`
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::states_tree::RespoStatesTree;
+use crate::{RespoError, RespoStore};
+
+use super::{load_store, unwrap_storage, wrap_storage, MemoryStorage, RespoStorage, StorageMigration};
+
+#[test]
+fn storage_is_migrated_from_saved_version() {
+  let migrations: Vec<StorageMigration> = vec![|s| Ok(format!("{s}+1")), |s| Ok(format!("{s}+2"))];
+  assert_eq!(unwrap_storage("legacy", &migrations), Ok("legacy+1+2".to_owned()));
+  assert_eq!(
+    unwrap_storage(&wrap_storage(1, "v1".to_owned()), &migrations),
+    Ok("v1+2".to_owned())
+  );
+  assert_eq!(unwrap_storage(&wrap_storage(2, "v2".to_owned()), &migrations), Ok("v2".to_owned()));
+  assert!(matches!(
+    unwrap_storage(&wrap_storage(3, "v3".to_owned()), &migrations),
+    Err(RespoError::StateRestore(_))
+  ));
+}
+
+#[derive(Debug, Clone, Default)]
+struct CountStore {
+  counted: i32,
+  states: RespoStatesTree,
+}
+
+impl RespoStore for CountStore {
+  type Action = ();
+  fn update(&mut self, _op: ()) -> Result<(), RespoError> {
+    Ok(())
+  }
+  fn get_states(&mut self) -> &mut RespoStatesTree {
+    &mut self.states
+  }
+  fn to_string(&self) -> String {
+    self.counted.to_string()
+  }
+  fn try_from_string(s: &str) -> Result<Self, RespoError> {
+    let counted = s.parse().map_err(|e| RespoError::StateRestore(format!("{e}")))?;
+    Ok(Self {
+      counted,
+      ..Default::default()
+    })
+  }
+}
+
+/// loads from `storage` like `RespoApp::try_load_storage` does, version 1 saved counts in tens
+fn load_count(storage: &MemoryStorage, store: &Rc<RefCell<CountStore>>) -> Result<(), RespoError> {
+  let migrations: Vec<StorageMigration> = vec![|s| Ok(format!("{s}0"))];
+  let result = Rc::new(RefCell::new(Ok(())));
+  load_store(Rc::new(storage.to_owned()), "count", migrations, store.to_owned(), {
+    let result = result.to_owned();
+    move |r| *result.borrow_mut() = r
+  });
+  result.replace(Ok(()))
+}
+
+#[test]
+fn store_is_loaded_and_migrated_from_storage() {
+  let storage = MemoryStorage::new();
+  let store = Rc::new(RefCell::new(CountStore::default()));
+
+  load_count(&storage, &store).expect("load nothing");
+  assert_eq!(store.borrow().counted, 0);
+
+  storage.save("count", "4").expect("save");
+  load_count(&storage, &store).expect("load");
+  assert_eq!(store.borrow().counted, 40);
+
+  storage.save("count", &wrap_storage(1, "5".to_owned())).expect("save");
+  load_count(&storage, &store).expect("load");
+  assert_eq!(store.borrow().counted, 5);
+
+  // broken content is kept aside, store is not replaced
+  storage.save("count", "broken").expect("save");
+  assert!(matches!(load_count(&storage, &store), Err(RespoError::StateRestore(_))));
+  assert_eq!(store.borrow().counted, 5);
+  assert_eq!(storage.get("count:failed"), Some("broken".to_owned()));
+}
@@ -0,0 +1,61 @@
+use web_sys::Storage;
+
+use crate::RespoError;
+
+use super::{RespoStorage, StorageLoadFn};
+
+/// `window.localStorage`, content is kept after the browser restarts, quota is about 5MB
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalStorage;
+
+/// `window.sessionStorage`, content is kept in the tab until it's closed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionStorage;
+
+fn get_window() -> Result<web_sys::Window, RespoError> {
+  web_sys::window().ok_or_else(|| RespoError::Dom(String::from("window not found")))
+}
+
+fn load_from(storage: Result<Storage, RespoError>, key: &str) -> Result<Option<String>, RespoError> {
+  Ok(storage?.get_item(key)?)
+}
+
+impl RespoStorage for LocalStorage {
+  fn load(&self, key: &str, done: StorageLoadFn) {
+    done(load_from(local_storage(), key))
+  }
+
+  fn save(&self, key: &str, content: &str) -> Result<(), RespoError> {
+    Ok(local_storage()?.set_item(key, content)?)
+  }
+
+  fn remove(&self, key: &str) -> Result<(), RespoError> {
+    Ok(local_storage()?.remove_item(key)?)
+  }
+}
+
+fn local_storage() -> Result<Storage, RespoError> {
+  get_window()?
+    .local_storage()?
+    .ok_or_else(|| RespoError::Dom(String::from("Failed to access local storage")))
+}
+
+impl RespoStorage for SessionStorage {
+  fn load(&self, key: &str, done: StorageLoadFn) {
+    done(load_from(session_storage(), key))
+  }
+
+  fn save(&self, key: &str, content: &str) -> Result<(), RespoError> {
+    Ok(session_storage()?.set_item(key, content)?)
+  }
+
+  fn remove(&self, key: &str) -> Result<(), RespoError> {
+    Ok(session_storage()?.remove_item(key)?)
+  }
+}
+
+fn session_storage() -> Result<Storage, RespoError> {
+  get_window()?
+    .session_storage()?
+    .ok_or_else(|| RespoError::Dom(String::from("Failed to access session storage")))
+}
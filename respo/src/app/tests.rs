@@ -0,0 +1,57 @@
+//! tests on wiring stores into apps
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::states_tree::{RespoStatesTree, RespoUpdateState};
+use crate::{RespoError, RespoStore};
+
+use super::dispatch_changes_store;
+
+/// a store that counts its own updates, states updates are not counted
+#[derive(Debug, Clone, Default)]
+struct VersionedStore {
+  counted: u64,
+  states: RespoStatesTree,
+}
+
+impl RespoStore for VersionedStore {
+  type Action = ();
+  fn update(&mut self, _op: ()) -> Result<(), RespoError> {
+    self.counted += 1;
+    Ok(())
+  }
+  fn get_states(&mut self) -> &mut RespoStatesTree {
+    &mut self.states
+  }
+  fn version(&self) -> Option<u64> {
+    Some(self.counted)
+  }
+  fn to_string(&self) -> String {
+    self.counted.to_string()
+  }
+  fn try_from_string(_s: &str) -> Result<Self, RespoError> {
+    Ok(Self::default())
+  }
+}
+
+#[test]
+fn states_update_changes_versioned_store() {
+  let store = Rc::new(RefCell::new(VersionedStore::default()));
+
+  let changed = dispatch_changes_store(&store, || Ok(())).expect("dispatch");
+  assert!(!changed, "nothing is updated");
+
+  let changed = dispatch_changes_store(&store, || {
+    store.borrow_mut().update_states(RespoUpdateState {
+      cursor: vec![Rc::from("panel")],
+      data: None,
+      backup: Some(serde_json::json!(true)),
+    });
+    Ok(())
+  })
+  .expect("dispatch");
+  assert!(changed, "states update renders again");
+
+  let changed = dispatch_changes_store(&store, || store.borrow_mut().update(())).expect("dispatch");
+  assert!(changed);
+}
@@ -0,0 +1,219 @@
+//! recording of dispatched actions, for stepping back and forth through history of the store
+
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use crate::{RespoError, RespoStore};
+
+#[cfg(test)]
+mod tests;
+
+/// updates a store with an action, usually `RespoApp::dispatch` of the app
+pub type StoreDispatchFn<S> = fn(Rc<RefCell<S>>, <S as RespoStore>::Action) -> Result<(), RespoError>;
+
+/// called after the store is replaced by a jump, the app uses it to render again
+type JumpListener = Box<dyn Fn()>;
+
+/// records actions dispatched to a store, along with a snapshot from `RespoStore::to_string`,
+/// so that the store can be restored to the point after any of the actions.
+///
+/// Return a clone from `RespoApp::time_travel` to record actions of an app,
+/// history starts from the store when the app starts rendering, and the app renders again after each jump.
+/// Dispatching after stepping back drops actions that were undone, like editors do with undo and redo.
+///
+/// ```rust
+/// use std::{cell::RefCell, rc::Rc};
+/// use respo::{states_tree::{RespoStatesTree, RespoUpdateState}, RespoAction, RespoError, RespoStore, RespoTimeTravel};
+///
+/// #[derive(Debug, Clone, Default)]
+/// struct Store { counted: i32, states: RespoStatesTree }
+///
+/// #[derive(Debug, Clone, Default)]
+/// enum Op { #[default] Noop, Add(i32) }
+///
+/// impl RespoAction for Op {
+///   type Intent = ();
+///   fn states_action(_a: RespoUpdateState) -> Self { Op::Noop }
+/// }
+///
+/// impl RespoStore for Store {
+///   type Action = Op;
+///   fn update(&mut self, op: Op) -> Result<(), RespoError> {
+///     if let Op::Add(n) = op { self.counted += n }
+///     Ok(())
+///   }
+///   fn get_states(&mut self) -> &mut RespoStatesTree { &mut self.states }
+///   fn to_string(&self) -> String { self.counted.to_string() }
+///   fn try_from_string(s: &str) -> Result<Self, RespoError> {
+///     let counted = s.parse().map_err(|e| RespoError::StateRestore(format!("{e}")))?;
+///     Ok(Store { counted, ..Default::default() })
+///   }
+/// }
+///
+/// let store = Rc::new(RefCell::new(Store::default()));
+/// let travel = RespoTimeTravel::new(&store, |store, op| store.borrow_mut().update(op));
+/// travel.dispatch(Op::Add(1)).unwrap();
+/// travel.dispatch(Op::Add(10)).unwrap();
+/// travel.step_back().unwrap();
+/// assert_eq!(store.borrow().counted, 1);
+/// travel.jump_to(0).unwrap();
+/// assert_eq!(store.borrow().counted, 0);
+/// travel.step_forward().unwrap();
+/// travel.dispatch(Op::Add(100)).unwrap();
+/// assert_eq!(store.borrow().counted, 101);
+/// assert_eq!(travel.actions_text(), ["Add(1)", "Add(100)"]);
+/// ```
+pub struct RespoTimeTravel<S>
+where
+  S: RespoStore,
+{
+  store: Rc<RefCell<S>>,
+  dispatch: StoreDispatchFn<S>,
+  history: Rc<RefCell<TimeTravelHistory<S::Action>>>,
+}
+
+struct TimeTravelHistory<T> {
+  snapshot: String,
+  actions: Vec<T>,
+  /// count of actions applied to the store
+  cursor: usize,
+  on_jump: Option<JumpListener>,
+}
+
+impl<S> Clone for RespoTimeTravel<S>
+where
+  S: RespoStore,
+{
+  fn clone(&self) -> Self {
+    Self {
+      store: self.store.to_owned(),
+      dispatch: self.dispatch,
+      history: self.history.to_owned(),
+    }
+  }
+}
+
+impl<S> Debug for RespoTimeTravel<S>
+where
+  S: RespoStore,
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let history = self.history.borrow();
+    f.debug_struct("RespoTimeTravel")
+      .field("snapshot", &history.snapshot)
+      .field("actions", &history.actions)
+      .field("cursor", &history.cursor)
+      .finish()
+  }
+}
+
+impl<S> RespoTimeTravel<S>
+where
+  S: RespoStore,
+{
+  /// starts recording with a snapshot of current store
+  pub fn new(store: &Rc<RefCell<S>>, dispatch: StoreDispatchFn<S>) -> Self {
+    let snapshot = store.borrow().to_string();
+    Self {
+      store: store.to_owned(),
+      dispatch,
+      history: Rc::new(RefCell::new(TimeTravelHistory {
+        snapshot,
+        actions: vec![],
+        cursor: 0,
+        on_jump: None,
+      })),
+    }
+  }
+
+  /// takes a new snapshot of current store and drops recorded actions
+  pub fn reset(&self) {
+    let snapshot = self.store.borrow().to_string();
+    let mut history = self.history.borrow_mut();
+    history.snapshot = snapshot;
+    history.actions.clear();
+    history.cursor = 0;
+  }
+
+  /// dispatches an action to the store, it's recorded when dispatching succeeds
+  pub fn dispatch(&self, action: S::Action) -> Result<(), RespoError> {
+    (self.dispatch)(self.store.to_owned(), action.to_owned())?;
+    let mut history = self.history.borrow_mut();
+    let cursor = history.cursor;
+    history.actions.truncate(cursor);
+    history.actions.push(action);
+    history.cursor += 1;
+    Ok(())
+  }
+
+  /// recorded actions, including those undone by stepping back
+  pub fn actions(&self) -> Vec<S::Action> {
+    self.history.borrow().actions.to_owned()
+  }
+
+  /// recorded actions in `Debug` format, for displaying in an inspector
+  pub fn actions_text(&self) -> Vec<String> {
+    self.history.borrow().actions.iter().map(|a| format!("{:?}", a)).collect()
+  }
+
+  /// count of actions applied to the store, `0` stands for the snapshot
+  pub fn cursor(&self) -> usize {
+    self.history.borrow().cursor
+  }
+
+  /// undoes last applied action, does nothing at the snapshot
+  pub fn step_back(&self) -> Result<(), RespoError> {
+    let cursor = self.cursor();
+    if cursor == 0 {
+      return Ok(());
+    }
+    self.jump_to(cursor - 1)
+  }
+
+  /// applies next action that was undone, does nothing at the latest action
+  pub fn step_forward(&self) -> Result<(), RespoError> {
+    let cursor = self.cursor();
+    if cursor >= self.history.borrow().actions.len() {
+      return Ok(());
+    }
+    self.jump_to(cursor + 1)
+  }
+
+  /// restores the store from the snapshot and replays first `cursor` actions, `0` restores the snapshot.
+  /// When an action fails in replaying, the store is put back and the cursor is not moved
+  pub fn jump_to(&self, cursor: usize) -> Result<(), RespoError> {
+    let (snapshot, actions) = {
+      let history = self.history.borrow();
+      if cursor > history.actions.len() {
+        return Err(RespoError::StateRestore(format!(
+          "no action at {}, only {} recorded",
+          cursor,
+          history.actions.len()
+        )));
+      }
+      (history.snapshot.to_owned(), history.actions[..cursor].to_vec())
+    };
+
+    let restored = S::try_from_string(&snapshot)?;
+    let previous = std::mem::replace(&mut *self.store.borrow_mut(), restored);
+    for action in actions {
+      if let Err(e) = (self.dispatch)(self.store.to_owned(), action) {
+        // a failed replay leaves the store and the cursor where they were
+        *self.store.borrow_mut() = previous;
+        return Err(e);
+      }
+    }
+    self.history.borrow_mut().cursor = cursor;
+
+    let history = self.history.borrow();
+    if let Some(f) = &history.on_jump {
+      f();
+    }
+    Ok(())
+  }
+
+  /// binds to an app that starts rendering, history starts from current store
+  pub(crate) fn attach(&self, on_jump: JumpListener) {
+    self.reset();
+    self.history.borrow_mut().on_jump = Some(on_jump);
+  }
+}
@@ -0,0 +1,138 @@
+//! tests on stepping through recorded actions
+
+use std::{
+  cell::{Cell, RefCell},
+  rc::Rc,
+};
+
+use crate::states_tree::{RespoStatesTree, RespoUpdateState};
+use crate::{RespoAction, RespoError, RespoStore};
+
+use super::RespoTimeTravel;
+
+#[derive(Debug, Clone, Default)]
+struct CountStore {
+  counted: i32,
+  states: RespoStatesTree,
+}
+
+#[derive(Debug, Clone, Default)]
+enum CountOp {
+  #[default]
+  Noop,
+  Add(i32),
+  /// fails while `REJECTING` is set, for breaking replays
+  Checked(i32),
+}
+
+thread_local! {
+  static REJECTING: Cell<bool> = const { Cell::new(false) };
+}
+
+impl RespoAction for CountOp {
+  type Intent = ();
+  fn states_action(_a: RespoUpdateState) -> Self {
+    CountOp::Noop
+  }
+}
+
+impl RespoStore for CountStore {
+  type Action = CountOp;
+  fn update(&mut self, op: CountOp) -> Result<(), RespoError> {
+    match op {
+      CountOp::Noop => {}
+      CountOp::Add(n) => self.counted += n,
+      CountOp::Checked(n) => {
+        if REJECTING.get() {
+          return Err(RespoError::Custom(format!("rejected adding {}", n)));
+        }
+        self.counted += n
+      }
+    }
+    Ok(())
+  }
+  fn get_states(&mut self) -> &mut RespoStatesTree {
+    &mut self.states
+  }
+  fn to_string(&self) -> String {
+    self.counted.to_string()
+  }
+  fn try_from_string(s: &str) -> Result<Self, RespoError> {
+    let counted = s.parse().map_err(|e| RespoError::StateRestore(format!("{e}")))?;
+    Ok(CountStore {
+      counted,
+      ..Default::default()
+    })
+  }
+}
+
+fn start_travel(counted: i32) -> (Rc<RefCell<CountStore>>, RespoTimeTravel<CountStore>) {
+  let store = Rc::new(RefCell::new(CountStore {
+    counted,
+    ..Default::default()
+  }));
+  let travel = RespoTimeTravel::new(&store, |store, op| store.borrow_mut().update(op));
+  (store, travel)
+}
+
+#[test]
+fn jump_to_goes_backward_and_forward() {
+  let (store, travel) = start_travel(5);
+  for n in [1, 10, 100] {
+    travel.dispatch(CountOp::Add(n)).expect("dispatch");
+  }
+  assert_eq!(store.borrow().counted, 116);
+  assert_eq!(travel.cursor(), 3);
+
+  travel.jump_to(1).expect("jump back");
+  assert_eq!(store.borrow().counted, 6);
+  assert_eq!(travel.cursor(), 1);
+
+  travel.jump_to(0).expect("jump to snapshot");
+  assert_eq!(store.borrow().counted, 5);
+
+  travel.jump_to(3).expect("jump forward");
+  assert_eq!(store.borrow().counted, 116);
+  assert_eq!(travel.cursor(), 3);
+  assert_eq!(travel.actions().len(), 3, "jumping keeps actions");
+
+  assert!(travel.jump_to(4).is_err(), "no action at 4");
+  assert_eq!(travel.cursor(), 3);
+}
+
+#[test]
+fn dispatch_after_jump_drops_undone_actions() {
+  let (store, travel) = start_travel(0);
+  for n in [1, 10, 100] {
+    travel.dispatch(CountOp::Add(n)).expect("dispatch");
+  }
+
+  travel.jump_to(1).expect("jump back");
+  travel.dispatch(CountOp::Add(1000)).expect("dispatch");
+  assert_eq!(store.borrow().counted, 1001);
+  assert_eq!(travel.cursor(), 2);
+  assert_eq!(travel.actions_text(), ["Add(1)", "Add(1000)"]);
+
+  travel.step_forward().expect("step forward");
+  assert_eq!(travel.cursor(), 2, "nothing to redo");
+}
+
+#[test]
+fn failed_replay_keeps_store_and_cursor() {
+  let (store, travel) = start_travel(0);
+  travel.dispatch(CountOp::Add(1)).expect("dispatch");
+  travel.dispatch(CountOp::Checked(10)).expect("dispatch");
+  travel.dispatch(CountOp::Add(100)).expect("dispatch");
+  travel.jump_to(0).expect("jump back");
+  assert_eq!(store.borrow().counted, 0);
+
+  REJECTING.set(true);
+  let result = travel.jump_to(3);
+  REJECTING.set(false);
+  assert!(result.is_err(), "replay fails at `Checked`, after `Add(1)` is applied");
+  assert_eq!(store.borrow().counted, 0, "store is put back");
+  assert_eq!(travel.cursor(), 0, "cursor is not moved");
+
+  travel.jump_to(3).expect("jump forward");
+  assert_eq!(store.borrow().counted, 111);
+}
@@ -0,0 +1,180 @@
+//! tracing of rendered frames, for building devtools
+
+use std::{cell::RefCell, collections::VecDeque, fmt::Debug, rc::Rc};
+
+use cirru_parser::{Cirru, CirruWriterOptions};
+
+use crate::app::util;
+
+#[cfg(test)]
+mod tests;
+
+/// what happened in a frame rendered after dispatching, times are in milliseconds
+#[derive(Debug, Clone)]
+pub struct RespoFrameTrace<T> {
+  /// actions dispatched since previous frame
+  pub actions: Vec<T>,
+  /// changes from diffing, one item for each change
+  pub changes: Cirru,
+  /// calling view and rendering memos
+  pub view_time: f64,
+  pub diff_time: f64,
+  pub patch_time: f64,
+}
+
+impl<T> RespoFrameTrace<T> {
+  pub fn new(actions: Vec<T>) -> Self {
+    Self {
+      actions,
+      changes: Cirru::List(vec![]),
+      view_time: 0.0,
+      diff_time: 0.0,
+      patch_time: 0.0,
+    }
+  }
+
+  /// changes formatted in Cirru text
+  pub fn changes_text(&self) -> String {
+    cirru_parser::format(&[self.changes.to_owned()], CirruWriterOptions { use_inline: true }).unwrap_or_else(|e| e.to_string())
+  }
+}
+
+/// receives traces of frames, enabled by returning it from `RespoApp::tracer`
+pub trait RespoTracer<T> {
+  fn trace(&self, frame: &RespoFrameTrace<T>);
+}
+
+/// prints a summary of each frame to console, along with actions and changes
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleTracer;
+
+impl<T> RespoTracer<T> for ConsoleTracer
+where
+  T: Debug,
+{
+  fn trace(&self, frame: &RespoFrameTrace<T>) {
+    let count = match &frame.changes {
+      Cirru::List(xs) => xs.len().saturating_sub(1),
+      Cirru::Leaf(_) => 0,
+    };
+    util::log!(
+      "frame: {} actions, {} changes, view {:.1}ms, diff {:.1}ms, patch {:.1}ms\nactions: {:?}\n{}",
+      frame.actions.len(),
+      count,
+      frame.view_time,
+      frame.diff_time,
+      frame.patch_time,
+      frame.actions,
+      frame.changes_text()
+    );
+  }
+}
+
+/// keeps traces of latest frames in memory, older ones are dropped when `capacity` is reached.
+/// Clones share the buffer, keep one to read frames in an inspector.
+///
+/// ```rust
+/// use respo::{RespoFrameTrace, RespoTracer, RingBufferTracer};
+///
+/// let tracer = RingBufferTracer::new(2);
+/// for action in ["a", "b", "c"] {
+///   tracer.trace(&RespoFrameTrace::new(vec![action]));
+/// }
+/// let actions: Vec<_> = tracer.frames().iter().flat_map(|f| f.actions.to_owned()).collect();
+/// assert_eq!(actions, ["b", "c"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RingBufferTracer<T> {
+  capacity: usize,
+  frames: Rc<RefCell<VecDeque<RespoFrameTrace<T>>>>,
+}
+
+impl<T> RingBufferTracer<T>
+where
+  T: Clone,
+{
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      frames: Rc::new(RefCell::new(VecDeque::with_capacity(capacity))),
+    }
+  }
+
+  /// traces in the buffer, from older ones to newer ones
+  pub fn frames(&self) -> Vec<RespoFrameTrace<T>> {
+    self.frames.borrow().iter().cloned().collect()
+  }
+
+  pub fn clear(&self) {
+    self.frames.borrow_mut().clear();
+  }
+}
+
+impl<T> RespoTracer<T> for RingBufferTracer<T>
+where
+  T: Clone,
+{
+  fn trace(&self, frame: &RespoFrameTrace<T>) {
+    if self.capacity == 0 {
+      return;
+    }
+    let mut frames = self.frames.borrow_mut();
+    while frames.len() >= self.capacity {
+      frames.pop_front();
+    }
+    frames.push_back(frame.to_owned());
+  }
+}
+
+/// tracer of an app, along with actions collected for next frame
+pub(crate) struct FrameRecorder<T> {
+  pub tracer: Rc<dyn RespoTracer<T>>,
+  actions: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> Clone for FrameRecorder<T> {
+  fn clone(&self) -> Self {
+    Self {
+      tracer: self.tracer.to_owned(),
+      actions: self.actions.to_owned(),
+    }
+  }
+}
+
+impl<T> FrameRecorder<T>
+where
+  T: Clone,
+{
+  pub fn new(tracer: Rc<dyn RespoTracer<T>>) -> Self {
+    Self {
+      tracer,
+      actions: Rc::new(RefCell::new(vec![])),
+    }
+  }
+
+  pub fn record_action(&self, action: &T) {
+    self.actions.borrow_mut().push(action.to_owned());
+  }
+
+  pub fn take_actions(&self) -> Vec<T> {
+    self.actions.take()
+  }
+}
+
+/// milliseconds from an arbitrary start, `performance.now()` in browser
+pub(crate) fn now() -> f64 {
+  #[cfg(target_arch = "wasm32")]
+  {
+    web_sys::window()
+      .and_then(|w| w.performance())
+      .map(|p| p.now())
+      .unwrap_or_else(js_sys::Date::now)
+  }
+  #[cfg(not(target_arch = "wasm32"))]
+  {
+    std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .map(|d| d.as_secs_f64() * 1000.0)
+      .unwrap_or(0.0)
+  }
+}
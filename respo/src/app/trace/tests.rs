@@ -0,0 +1,46 @@
+//! tests on tracing frames, rendered into `MemoryBackend`
+
+use crate::app::backend::{DomBackend, MemoryBackend};
+use crate::app::renderer::RespoRenderer;
+use crate::node::{DispatchFn, RespoIndexKey, RespoNode};
+use crate::{div, span};
+
+use super::{RespoFrameTrace, RespoTracer, RingBufferTracer};
+
+fn list(keys: &[&str]) -> RespoNode<()> {
+  div()
+    .children_indexed(keys.iter().map(|k| (RespoIndexKey::from(*k), span().inner_text(*k).to_node())))
+    .to_node()
+}
+
+#[test]
+fn traced_update_records_changes_and_times() {
+  let backend = MemoryBackend::new();
+  let mount_target = backend.create_element("div").expect("create mount target");
+  let dispatch = DispatchFn::new(|_: ()| Ok(()));
+  let mut renderer = RespoRenderer::mount(backend, mount_target, list(&["a"]), dispatch).expect("mount");
+
+  let tracer = RingBufferTracer::new(4);
+  let mut trace = RespoFrameTrace::new(vec![()]);
+  renderer.update_traced(list(&["a", "b"]), &mut trace).expect("update");
+  tracer.trace(&trace);
+
+  let frames = tracer.frames();
+  assert_eq!(frames.len(), 1);
+  assert_eq!(frames[0].actions, [()]);
+  assert!(frames[0].changes_text().contains("modify-children"), "{}", frames[0].changes_text());
+  assert!(frames[0].view_time >= 0.0 && frames[0].diff_time >= 0.0 && frames[0].patch_time >= 0.0);
+}
+
+#[test]
+fn ring_buffer_keeps_latest_frames() {
+  let tracer = RingBufferTracer::new(2);
+  for n in 0..3 {
+    tracer.trace(&RespoFrameTrace::new(vec![n]));
+  }
+  let actions = tracer.frames().into_iter().flat_map(|f| f.actions).collect::<Vec<_>>();
+  assert_eq!(actions, [1, 2]);
+
+  tracer.clear();
+  assert!(tracer.frames().is_empty());
+}
@@ -1,3 +1,4 @@
+use crate::RespoError;
 use std::{cell::RefCell, rc::Rc};
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
@@ -5,7 +6,7 @@ use web_sys::Node;
 
 /// this one uses `requestAnimationFrame` for calling
 #[allow(dead_code)]
-pub fn raf_loop(mut cb: Box<dyn FnMut() -> Result<(), String>>) {
+pub fn raf_loop(mut cb: Box<dyn FnMut() -> Result<(), RespoError>>) {
   let f_ = Rc::new(RefCell::new(None));
   let g = f_.to_owned();
 
@@ -34,7 +35,7 @@ fn window() -> web_sys::Window {
 /// uses `requestAnimationFrame` for calling, but with a interval to reduce cost.
 /// prefer `req_loop` if you want to be faster
 #[allow(dead_code)]
-pub fn raf_loop_slow(interval: i32, mut cb: Box<dyn FnMut() -> Result<(), String>>) {
+pub fn raf_loop_slow(interval: i32, mut cb: Box<dyn FnMut() -> Result<(), RespoError>>) {
   let f = Rc::new(RefCell::new(None));
   let g = f.to_owned();
 
@@ -63,13 +64,8 @@ pub fn raf_loop_slow(interval: i32, mut cb: Box<dyn FnMut() -> Result<(), String
   request_animation_frame(g.borrow().as_ref().expect("call raq"));
 }
 
-// just get first of tuple
-pub(crate) fn fst<T, U>(pair: &(T, U)) -> &T {
-  &pair.0
-}
-
 /// a shorthand for get an Node with given pattern
-pub fn query_select_node(pattern: &str) -> Result<Node, String> {
+pub fn query_select_node(pattern: &str) -> Result<Node, RespoError> {
   let window = web_sys::window().expect("no global `window` exists");
   let document = window.document().expect("should have a document on window");
   let target = document.query_selector(pattern).expect("call selector").expect("find .app");
@@ -77,11 +73,11 @@ pub fn query_select_node(pattern: &str) -> Result<Node, String> {
   if let Some(element) = target.dyn_ref::<Node>() {
     Ok(element.to_owned())
   } else {
-    Err(format!("failed to find {}", pattern))
+    Err(RespoError::Dom(format!("failed to find {}", pattern)))
   }
 }
 
-/// wraps on top of `web_sys::console.log_1`.
+/// wraps on top of `web_sys::console.log_1`, prints to stdout on native targets.
 ///
 /// use it like:
 /// ```ignore
@@ -90,11 +86,14 @@ pub fn query_select_node(pattern: &str) -> Result<Node, String> {
 #[macro_export]
 macro_rules! log {
   ($($t:tt)*) => {{
+    #[cfg(target_arch = "wasm32")]
     web_sys::console::log_1(&format!($($t)*).into());
+    #[cfg(not(target_arch = "wasm32"))]
+    println!($($t)*);
   }};
 }
 
-/// wraps on top of `web_sys::console.warn_1`.
+/// wraps on top of `web_sys::console.warn_1`, prints to stderr on native targets.
 ///
 /// use it like:
 /// ```ignore
@@ -103,11 +102,14 @@ macro_rules! log {
 #[macro_export]
 macro_rules! warn_log {
   ($($t:tt)*) => {{
+    #[cfg(target_arch = "wasm32")]
     web_sys::console::warn_1(&format!($($t)*).into());
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!($($t)*);
   }};
 }
 
-/// wraps on top of `web_sys::console.error_1`.
+/// wraps on top of `web_sys::console.error_1`, prints to stderr on native targets.
 ///
 /// use it like:
 /// ```ignore
@@ -116,7 +118,10 @@ macro_rules! warn_log {
 #[macro_export]
 macro_rules! error_log {
   ($($t:tt)*) => {{
+    #[cfg(target_arch = "wasm32")]
     web_sys::console::error_1(&format!($($t)*).into());
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!($($t)*);
   }};
 }
 
@@ -0,0 +1,86 @@
+//! errors returned from Respo APIs
+
+use std::fmt::Display;
+
+use wasm_bindgen::JsValue;
+
+/// error type of Respo, variants tell where the failure comes from.
+///
+/// Strings from app code are converted into `Custom`, and exceptions thrown by browser APIs into `Dom`,
+/// so `?` works on both inside handlers and effects:
+///
+/// ```rust
+/// use respo::RespoError;
+///
+/// fn parse(s: &str) -> Result<u32, RespoError> {
+///   let n = s.parse::<u32>().map_err(|e| e.to_string())?;
+///   Ok(n)
+/// }
+/// assert_eq!(parse("x"), Err(RespoError::Custom("invalid digit found in string".to_owned())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RespoError {
+  /// coord does not lead to a node in the tree, or leads to a node of another kind
+  CoordMismatch(String),
+  /// no event handler found on the target element
+  MissingHandler { event: String, element: String },
+  /// changes from diffing do not apply to the rendered tree
+  Patch(String),
+  /// failed to restore states from backup or storage
+  StateRestore(String),
+  /// state in the tree is of another type than the one being read
+  StateMismatch(String),
+  /// exception thrown by browser APIs
+  Dom(String),
+  /// DOM is not in the shape the renderer left it, probably modified by other scripts.
+  /// Raised by tree operations of backends and by patching, never passed on from effects, the renderer rebuilds DOM on it
+  DomInconsistent(String),
+  /// failures in dialog plugins
+  Plugin(String),
+  /// errors from app code
+  Custom(String),
+}
+
+impl Display for RespoError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::CoordMismatch(s) => write!(f, "coord mismatch: {}", s),
+      Self::MissingHandler { event, element } => write!(f, "no handler for event '{}' on element '{}'", event, element),
+      Self::Patch(s) => write!(f, "failed to patch: {}", s),
+      Self::StateRestore(s) => write!(f, "failed to restore states: {}", s),
+      Self::StateMismatch(s) => write!(f, "state type mismatch: {}", s),
+      Self::Dom(s) => write!(f, "DOM exception: {}", s),
+      Self::DomInconsistent(s) => write!(f, "DOM is inconsistent: {}", s),
+      Self::Plugin(s) => write!(f, "plugin error: {}", s),
+      Self::Custom(s) => write!(f, "{}", s),
+    }
+  }
+}
+
+impl std::error::Error for RespoError {}
+
+impl RespoError {
+  /// DOM is not in the shape of the virtual DOM, probably modified by other scripts, renderer rebuilds DOM on such errors.
+  /// Exceptions from effects and listeners are not counted, rebuilding does not fix them
+  pub fn is_dom_inconsistency(&self) -> bool {
+    matches!(self, Self::DomInconsistent(_))
+  }
+}
+
+impl From<String> for RespoError {
+  fn from(s: String) -> Self {
+    Self::Custom(s)
+  }
+}
+
+impl From<JsValue> for RespoError {
+  fn from(v: JsValue) -> Self {
+    Self::Dom(v.as_string().unwrap_or_else(|| format!("{:?}", v)))
+  }
+}
+
+impl From<RespoError> for JsValue {
+  fn from(e: RespoError) -> Self {
+    JsValue::from_str(&e.to_string())
+  }
+}
@@ -10,6 +10,7 @@
 //! - there's Virtual DOM, although simplified, still flexible for declarative UI
 //! - CSS with Rust macros, I call it "CSS in Rust"
 //! - Effects, flow from data to DOM, for patching DOM manually on data change
+//! - `RespoComponent::boundary` renders a fallback when building part of the view fails, instead of stopping the app
 //! - `respo::ui` provides basic style. Also try Modal, dialog, drawer components.
 //!
 //! To manage states:
@@ -17,10 +18,13 @@
 //! - Rust enum and pattern matching it really nice for Elm-style action dispatching
 //! - global states tree and cursor to maintain states, may not be familiar but still being handy
 //! - you may also write shared component like a "plugin" to manage states.
+//! - `RespoApp::time_travel` records dispatched actions, to step back and forth or jump to any point in history
 //!
 //! To optimize:
 //!
 //! - components and elements are in functions, available for [memoize](https://crates.io/crates/memoize)
+//! - `RespoComponent::memo(name, deps, render)` skips rendering and diffing of a subtree when `deps` are unchanged
+//! - `RespoApp::tracer` receives actions, DOM changes and times spent of each frame, try `ConsoleTracer` or `RingBufferTracer`
 //! - well, it's Rust, you can do more...
 //!
 //! Meanwhile it does not support React features such as:
@@ -31,17 +35,23 @@
 //!
 //! Rust and WebAssembly lacks tricks for hot reloading,
 //! it's suggested to use [trunk](https://github.com/trunk-rs/trunk) to edit and reload the project during development.
-//! App states including components states can be saved to local storage and reloaded.
+//! App states including components states can be saved to local storage and reloaded,
+//! other storages like IndexedDB are picked with `RespoApp::get_storage`.
 //!
 //! To start project, create your structs to implement traits:
 //!
 //! - `RespoStore` for global states and states tree, and `RespoAction` for updating
 //! - `RespoApp` for MVC overview of the app, and more views, bind events
 //!
-//! say app is called `app`, you start app with `app.render_loop()`.
+//! say app is called `app`, you start app with `app.render_loop()`, which returns a handle to `.unmount()` the app later.
+//! For server-side rendering, `render_to_html(&tree)` turns a virtual DOM tree into HTML without a browser,
+//! and `app.hydrate_loop()` picks up the HTML in browser instead of rendering again.
 //! Check [Workflow](https://github.com/Respo/respo-rust-workflow/tree/c7cc0c0/src) for a working example.
 
+extern crate self as respo;
+
 mod app;
+mod error;
 pub mod states_tree;
 
 pub(crate) mod node;
@@ -50,4 +60,9 @@ pub mod ui;
 pub use node::element::alias::*;
 pub use node::*;
 
-pub use app::{util, RespoApp, RespoStore};
+pub use app::{util, RespoApp, RespoAppHandle, RespoStore};
+pub use app::{ConsoleTracer, RespoFrameTrace, RespoTracer, RingBufferTracer};
+pub use app::{DomBackend, MemoryBackend, MemoryNode, RespoEventCallback, RespoRenderer, WebBackend};
+pub use app::{IndexedDbStorage, LocalStorage, MemoryStorage, RespoStorage, SessionStorage, StorageLoadFn};
+pub use app::{RespoTimeTravel, StorageMigration, StoreDispatchFn};
+pub use error::RespoError;
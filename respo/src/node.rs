@@ -4,6 +4,7 @@ pub(crate) mod component;
 pub mod css;
 pub(crate) mod dom_change;
 pub(crate) mod element;
+pub(crate) mod html;
 mod listener;
 
 use std::boxed::Box;
@@ -12,20 +13,24 @@ use std::rc::Rc;
 use std::{collections::HashMap, fmt::Debug};
 
 use cirru_parser::Cirru;
-pub use listener::RespoEvent;
+pub use listener::{RespoEvent, RespoEventOptions};
 pub(crate) use listener::{RespoEventMark, RespoListenerFn};
 
+pub use component::boundary::RespoBoundary;
+pub use component::memo::{RespoMemo, RespoMemoDeps};
 pub use component::RespoComponent;
 pub use element::RespoElement;
+pub use html::render_to_html;
 
 use crate::states_tree::{DynEq, RespoStateBranch, RespoUpdateState};
+use crate::RespoError;
 
 use css::respo_style;
 
 pub(crate) use dom_change::RespoCoord;
 pub(crate) use dom_change::{ChildDomOp, DomChange};
 
-pub use component::effect::{RespoEffect, RespoEffectType};
+pub use component::effect::{RespoEffect, RespoEffectNode, RespoEffectType};
 pub use css::ConvertRespoCssSize;
 
 /// an `Element` or a `Component`
@@ -38,6 +43,8 @@ where
   /// corresponding to DOM elements
   Element(RespoElement<T>),
   Referenced(Rc<RespoNode<T>>),
+  /// component rendered only when dependencies change, resolved by renderer before diffing
+  Memo(RespoMemo<T>),
 }
 
 impl<T> From<RespoNode<T>> for Cirru
@@ -57,6 +64,11 @@ where
         Cirru::List(xs)
       }
       RespoNode::Referenced(cell) => (*cell).to_owned().into(),
+      RespoNode::Memo(RespoMemo { name, deps, .. }) => Cirru::List(vec![
+        Cirru::Leaf("::Memo".into()),
+        Cirru::from(name.as_ref()),
+        Cirru::from(format!("{:?}", deps)),
+      ]),
     }
   }
 }
@@ -71,7 +83,7 @@ where
 }
 
 /// a key for referencing a child node, use a value that can be converted to string
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct RespoIndexKey(String);
 
 impl From<usize> for RespoIndexKey {
@@ -136,6 +148,7 @@ where
       name: name.into(),
       effects: Vec::new(),
       tree: Box::new(tree),
+      boundary: None,
     })
   }
   /// wrap with a `Rc<T>` to enable memory reuse and skipping in diff
@@ -157,7 +170,7 @@ pub(crate) fn str_dict_to_cirrus_dict(dict: &StrDict) -> Cirru {
 /// dispatch function passed from root of renderer,
 /// call it like `dispatch.run(op)`
 #[derive(Clone)]
-pub struct DispatchFn<T>(Rc<dyn Fn(T) -> Result<(), String>>)
+pub struct DispatchFn<T>(Rc<dyn Fn(T) -> Result<(), RespoError>>)
 where
   T: Debug + Clone;
 
@@ -213,11 +226,11 @@ where
   T: Debug + Clone + RespoAction,
 {
   /// dispatch an action
-  pub fn run(&self, op: T) -> Result<(), String> {
+  pub fn run(&self, op: T) -> Result<(), RespoError> {
     (self.0)(op)
   }
   /// dispatch to update local state
-  pub fn run_state<U>(&self, cursor: &[Rc<str>], data: U) -> Result<(), String>
+  pub fn run_state<U>(&self, cursor: &[Rc<str>], data: U) -> Result<(), RespoError>
   where
     U: DynEq + ToOwned + Clone + PartialEq + Eq + 'static,
   {
@@ -225,16 +238,16 @@ where
     (self.0)(T::build_states_action(cursor, Some(RespoStateBranch::new(a))))
   }
   /// alias for dispatching intent
-  pub fn run_intent(&self, op: T::Intent) -> Result<(), String> {
+  pub fn run_intent(&self, op: T::Intent) -> Result<(), RespoError> {
     (self.0)(T::build_intent_action(op))
   }
   /// reset state to empty
-  pub fn run_empty_state(&self, cursor: &[Rc<str>]) -> Result<(), String> {
+  pub fn run_empty_state(&self, cursor: &[Rc<str>]) -> Result<(), RespoError> {
     (self.0)(T::build_states_action(cursor, None))
   }
   pub fn new<U>(f: U) -> Self
   where
-    U: Fn(T) -> Result<(), String> + 'static,
+    U: Fn(T) -> Result<(), RespoError> + 'static,
   {
     Self(Rc::new(f))
   }
@@ -242,7 +255,7 @@ where
 
 /// (internal) function to handle event marks at first phase of event handling
 #[derive(Clone)]
-pub(crate) struct RespoEventMarkFn(Rc<dyn Fn(RespoEventMark) -> Result<(), String>>);
+pub(crate) struct RespoEventMarkFn(Rc<dyn Fn(RespoEventMark) -> Result<(), RespoError>>);
 
 impl Debug for RespoEventMarkFn {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -251,19 +264,19 @@ impl Debug for RespoEventMarkFn {
 }
 
 impl RespoEventMarkFn {
-  pub fn run(&self, e: RespoEventMark) -> Result<(), String> {
+  pub fn run(&self, e: RespoEventMark) -> Result<(), RespoError> {
     (self.0)(e)
   }
   pub fn new<U>(f: U) -> Self
   where
-    U: Fn(RespoEventMark) -> Result<(), String> + 'static,
+    U: Fn(RespoEventMark) -> Result<(), RespoError> + 'static,
   {
     Self(Rc::new(f))
   }
 }
 
-impl From<Rc<dyn Fn(RespoEventMark) -> Result<(), String>>> for RespoEventMarkFn {
-  fn from(f: Rc<dyn Fn(RespoEventMark) -> Result<(), String>>) -> Self {
+impl From<Rc<dyn Fn(RespoEventMark) -> Result<(), RespoError>>> for RespoEventMarkFn {
+  fn from(f: Rc<dyn Fn(RespoEventMark) -> Result<(), RespoError>>) -> Self {
     Self(f)
   }
 }
@@ -1,7 +1,10 @@
+pub(crate) mod boundary;
 pub(crate) mod effect;
+pub(crate) mod memo;
 
 use std::{fmt::Debug, rc::Rc};
 
+use boundary::BoundaryCatch;
 use effect::RespoEffectBox;
 
 use crate::{RespoEffect, RespoElement, RespoNode};
@@ -15,6 +18,8 @@ where
   pub name: Rc<str>,
   pub effects: Vec<RespoEffectBox>,
   pub tree: Box<RespoNode<T>>,
+  /// set by boundaries, errors of memos inside are caught when memos are rendered
+  pub(crate) boundary: Option<BoundaryCatch<T>>,
 }
 
 impl<T> From<RespoComponent<T>> for RespoNode<T>
@@ -35,6 +40,7 @@ where
       name: Rc::from(name),
       effects: vec![],
       tree: Box::new(tree.to_node()),
+      boundary: None,
     }
   }
   pub fn to_node(self) -> RespoNode<T> {
@@ -46,10 +52,20 @@ where
   where
     S: RespoEffect + 'static,
   {
-    let RespoComponent { name, mut effects, tree } = self;
+    let RespoComponent {
+      name,
+      mut effects,
+      tree,
+      boundary,
+    } = self;
     {
       effects.push(RespoEffectBox::new(eff));
-      RespoComponent { name, effects, tree }
+      RespoComponent {
+        name,
+        effects,
+        tree,
+        boundary,
+      }
     }
   }
 }
@@ -0,0 +1,180 @@
+//! error boundary, renders a fallback when building its child fails
+
+use std::{fmt::Debug, rc::Rc};
+
+use crate::states_tree::note_caught_error;
+use crate::{app::util, RespoComponent, RespoError, RespoNode};
+
+type FallbackFn<T> = Rc<dyn Fn(&RespoError) -> RespoNode<T>>;
+type ErrorHookFn = Rc<dyn Fn(&RespoError)>;
+
+/// result of building a subtree inside a boundary, created by [RespoComponent::boundary].
+///
+/// Errors are reported by `on_error`, or logged as warnings when no hook is given.
+pub struct RespoBoundary<T>
+where
+  T: Debug + Clone,
+{
+  name: Rc<str>,
+  result: Result<RespoNode<T>, RespoError>,
+  fallback: FallbackFn<T>,
+  on_error: Option<ErrorHookFn>,
+}
+
+impl<T> Debug for RespoBoundary<T>
+where
+  T: Debug + Clone,
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RespoBoundary")
+      .field("name", &self.name)
+      .field("result", &self.result)
+      .finish()
+  }
+}
+
+impl<T> RespoBoundary<T>
+where
+  T: Debug + Clone,
+{
+  /// calls `f` with the error when building failed, it's kept for errors of memos inside as well
+  pub fn on_error<F>(mut self, f: F) -> Self
+  where
+    F: Fn(&RespoError) + 'static,
+  {
+    if let Err(e) = &self.result {
+      f(e);
+    }
+    self.on_error = Some(Rc::new(f));
+    self
+  }
+
+  /// a component of boundary name, containing the built subtree or the fallback
+  pub fn to_node(self) -> RespoNode<T> {
+    let (tree, boundary) = match self.result {
+      Ok(tree) => (
+        tree,
+        Some(BoundaryCatch {
+          fallback: self.fallback,
+          on_error: self.on_error,
+        }),
+      ),
+      Err(e) => {
+        if self.on_error.is_none() {
+          util::warn_log!("error caught by boundary {}: {}", self.name, e);
+        }
+        ((self.fallback)(&e), None)
+      }
+    };
+    RespoNode::Component(RespoComponent {
+      name: self.name,
+      effects: vec![],
+      tree: Box::new(tree),
+      boundary,
+    })
+  }
+}
+
+/// kept in the component of a boundary that built its subtree, memos inside are rendered later
+/// by the renderer, their errors are caught with this
+#[derive(Clone)]
+pub(crate) struct BoundaryCatch<T>
+where
+  T: Debug + Clone,
+{
+  fallback: FallbackFn<T>,
+  on_error: Option<ErrorHookFn>,
+}
+
+impl<T> BoundaryCatch<T>
+where
+  T: Debug + Clone,
+{
+  /// reports the error of the boundary named `name`, returns the fallback to render in place
+  pub fn catch(&self, name: &str, e: &RespoError) -> RespoNode<T> {
+    // states of the failed part are not picked, they should not be swept
+    note_caught_error();
+    match &self.on_error {
+      Some(f) => f(e),
+      None => util::warn_log!("error caught by boundary {}: {}", name, e),
+    }
+    (self.fallback)(e)
+  }
+}
+
+impl<T> Debug for BoundaryCatch<T>
+where
+  T: Debug + Clone,
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoundaryCatch")
+  }
+}
+
+/// handlers are treated as equal, like listeners, they are not compared in diffing
+impl<T> PartialEq for BoundaryCatch<T>
+where
+  T: Debug + Clone,
+{
+  fn eq(&self, _other: &Self) -> bool {
+    true
+  }
+}
+
+impl<T> Eq for BoundaryCatch<T> where T: Debug + Clone {}
+
+impl<T> From<RespoBoundary<T>> for RespoNode<T>
+where
+  T: Debug + Clone,
+{
+  fn from(boundary: RespoBoundary<T>) -> Self {
+    boundary.to_node()
+  }
+}
+
+impl<T> RespoComponent<T>
+where
+  T: Debug + Clone,
+{
+  /// builds a subtree with `build`, an `Err` from it is caught and `fallback` is rendered in place,
+  /// so a broken part does not stop rendering of the whole app.
+  ///
+  /// Memos inside are rendered later by the renderer, their errors are caught by the boundary as well,
+  /// `fallback` and `on_error` are called again then.
+  ///
+  /// ```rust
+  /// use std::{cell::RefCell, rc::Rc};
+  /// use respo::{div, span, RespoComponent, RespoError, RespoNode};
+  ///
+  /// fn comp_broken() -> Result<RespoNode<()>, RespoError> {
+  ///   Err(RespoError::Custom("broken".to_owned()))
+  /// }
+  ///
+  /// let caught = Rc::new(RefCell::new(None));
+  /// let tree: RespoNode<()> = RespoComponent::boundary("panel", comp_broken, |e| span().inner_text(e.to_string()).to_node())
+  ///   .on_error({
+  ///     let caught = caught.to_owned();
+  ///     move |e| *caught.borrow_mut() = Some(e.to_owned())
+  ///   })
+  ///   .to_node();
+  /// assert_eq!(caught.take(), Some(RespoError::Custom("broken".to_owned())));
+  /// assert_eq!(respo::render_to_html(&tree).unwrap(), "<span>broken</span>");
+  /// ```
+  pub fn boundary<F, G>(name: &str, build: F, fallback: G) -> RespoBoundary<T>
+  where
+    F: FnOnce() -> Result<RespoNode<T>, RespoError>,
+    G: Fn(&RespoError) -> RespoNode<T> + 'static,
+  {
+    let result = build();
+    if result.is_err() {
+      // states of the failed part are not picked, they should not be swept
+      note_caught_error();
+    }
+    RespoBoundary {
+      name: Rc::from(name),
+      result,
+      fallback: Rc::new(fallback),
+      on_error: None,
+    }
+  }
+}
@@ -2,10 +2,40 @@ mod base;
 
 use std::{any::Any, fmt::Debug, rc::Rc};
 
+use crate::app::backend::MemoryNode;
+use crate::RespoError;
 use base::RespoEffectDynEq;
 use cirru_parser::Cirru;
 use web_sys::Node;
 
+/// element of the component that an effect runs on, handed over by the backend that renders the app.
+/// Effects that touch DOM read `web_node()` and skip when it's `None`, so they still run with `MemoryBackend`
+#[derive(Debug, Clone, Copy)]
+pub enum RespoEffectNode<'a> {
+  /// from `WebBackend`
+  Web(&'a Node),
+  /// from `MemoryBackend`
+  Memory(&'a MemoryNode),
+}
+
+impl<'a> RespoEffectNode<'a> {
+  /// the DOM node in browser, `None` when rendered by `MemoryBackend`
+  pub fn web_node(&self) -> Option<&'a Node> {
+    match self {
+      Self::Web(el) => Some(el),
+      Self::Memory(_) => None,
+    }
+  }
+
+  /// the node kept by `MemoryBackend`, `None` in browser
+  pub fn memory_node(&self) -> Option<&'a MemoryNode> {
+    match self {
+      Self::Web(_) => None,
+      Self::Memory(el) => Some(el),
+    }
+  }
+}
+
 /// trait for component effects
 /// you can declare `mounted`, `beforeUpdate`, `updated`, `beforeUnmount` methods
 /// to handle lifecycle events, mainly for manually manipulating DOM
@@ -15,7 +45,7 @@ where
 {
   /// actually run effect
   #[allow(unused_variables)]
-  fn run(&self, effect_type: RespoEffectType, el: &Node) -> Result<(), String> {
+  fn run(&self, effect_type: RespoEffectType, el: RespoEffectNode) -> Result<(), RespoError> {
     match effect_type {
       RespoEffectType::Mounted => self.mounted(el),
       RespoEffectType::BeforeUpdate => self.before_update(el),
@@ -25,22 +55,22 @@ where
   }
   /// called when mounted
   #[allow(unused_variables)]
-  fn mounted(&self, el: &Node) -> Result<(), String> {
+  fn mounted(&self, el: RespoEffectNode) -> Result<(), RespoError> {
     Ok(())
   }
   /// called when before update
   #[allow(unused_variables)]
-  fn before_update(&self, el: &Node) -> Result<(), String> {
+  fn before_update(&self, el: RespoEffectNode) -> Result<(), RespoError> {
     Ok(())
   }
   /// called when updated
   #[allow(unused_variables)]
-  fn updated(&self, el: &Node) -> Result<(), String> {
+  fn updated(&self, el: RespoEffectNode) -> Result<(), RespoError> {
     Ok(())
   }
   /// called when before unmount
   #[allow(unused_variables)]
-  fn before_unmount(&self, el: &Node) -> Result<(), String> {
+  fn before_unmount(&self, el: RespoEffectNode) -> Result<(), RespoError> {
     Ok(())
   }
 }
@@ -0,0 +1,127 @@
+//! memoized component, its subtree is only rendered again when dependencies change
+
+use std::{any::Any, fmt::Debug, rc::Rc};
+
+use crate::{RespoComponent, RespoError, RespoNode};
+
+/// dependencies of a memo, compared as trait objects
+pub trait RespoMemoDeps
+where
+  Self: Debug + Any + 'static,
+{
+  fn as_any(&self) -> &dyn Any;
+  fn do_eq(&self, rhs: &dyn RespoMemoDeps) -> bool;
+}
+
+impl<T> RespoMemoDeps for T
+where
+  T: PartialEq + Debug + 'static,
+{
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+
+  fn do_eq(&self, rhs: &dyn RespoMemoDeps) -> bool {
+    match rhs.as_any().downcast_ref::<Self>() {
+      Some(rhs_concrete) => self == rhs_concrete,
+      None => false,
+    }
+  }
+}
+
+impl PartialEq for dyn RespoMemoDeps {
+  fn eq(&self, rhs: &Self) -> bool {
+    self.do_eq(rhs)
+  }
+}
+
+type MemoRenderFn<T> = Rc<dyn Fn() -> Result<RespoNode<T>, RespoError>>;
+
+/// a component whose subtree is rendered lazily, created by [RespoComponent::memo].
+///
+/// The renderer keeps the subtree rendered at each coord, when `deps` equal to the previous ones,
+/// the subtree is reused, and diffing is skipped since it's the same reference.
+#[derive(Clone)]
+pub struct RespoMemo<T>
+where
+  T: Debug + Clone,
+{
+  pub name: Rc<str>,
+  pub deps: Rc<dyn RespoMemoDeps>,
+  render: MemoRenderFn<T>,
+}
+
+impl<T> Debug for RespoMemo<T>
+where
+  T: Debug + Clone,
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("RespoMemo")
+      .field("name", &self.name)
+      .field("deps", &self.deps)
+      .finish()
+  }
+}
+
+/// render functions are not compared
+impl<T> PartialEq for RespoMemo<T>
+where
+  T: Debug + Clone,
+{
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name && *self.deps == *other.deps
+  }
+}
+
+impl<T> Eq for RespoMemo<T> where T: Debug + Clone {}
+
+impl<T> RespoMemo<T>
+where
+  T: Debug + Clone,
+{
+  /// renders the subtree, without reading cache
+  pub fn render(&self) -> Result<RespoNode<T>, RespoError> {
+    (self.render)()
+  }
+
+  /// renders into a component of the same name, used where no cache is available
+  pub fn to_component(&self) -> Result<RespoNode<T>, RespoError> {
+    Ok(RespoNode::Component(RespoComponent {
+      name: self.name.to_owned(),
+      effects: vec![],
+      tree: Box::new(self.render()?),
+      boundary: None,
+    }))
+  }
+}
+
+impl<T> RespoComponent<T>
+where
+  T: Debug + Clone,
+{
+  /// creates a component that calls `render` only when `deps` differ from the ones rendered at the same place last time,
+  /// otherwise both the view and diffing of its subtree are skipped.
+  ///
+  /// `render` should only read from `deps`, values captured otherwise are not compared.
+  ///
+  /// ```rust
+  /// use respo::{span, RespoComponent, RespoNode};
+  ///
+  /// fn comp_count(count: u32) -> RespoNode<()> {
+  ///   RespoComponent::memo("count", count, move || Ok(span().inner_text(format!("{count}")).to_node()))
+  /// }
+  /// assert_eq!(comp_count(1), comp_count(1));
+  /// assert_ne!(comp_count(1), comp_count(2));
+  /// ```
+  pub fn memo<D, F>(name: &str, deps: D, render: F) -> RespoNode<T>
+  where
+    D: PartialEq + Debug + 'static,
+    F: Fn() -> Result<RespoNode<T>, RespoError> + 'static,
+  {
+    RespoNode::Memo(RespoMemo {
+      name: Rc::from(name),
+      deps: Rc::new(deps),
+      render: Rc::new(render),
+    })
+  }
+}
@@ -19,24 +19,18 @@
 //! then `style_done_button()` returns the class name, while CSS is generated and injected into the `<style/>`.
 
 mod css_size;
+mod registry;
 
 use std::{
-  collections::HashSet,
   fmt::{self, Display, Formatter, Write},
   rc::Rc,
-  sync::RwLock,
 };
 
 use css_size::CssPx;
 use hsluv::hsluv_to_rgb;
-use wasm_bindgen::JsCast;
-use web_sys::Element;
 
 pub use css_size::{ConvertRespoCssSize, CssSize};
-
-lazy_static::lazy_static! {
-  static ref CLASS_NAME_IN_TAGS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
-}
+pub(crate) use registry::StyleRegistry;
 
 /// it provides ADT interfaces as APIs, but internally it's maintained dynamically.
 /// it's easily diffed in a hashmap.
@@ -692,7 +686,8 @@ impl Display for CssTextDecoration {
 }
 
 /// does internal work inside the macro `static_style!(name, &styles)`.
-/// inserts CSS as `<style .. />` under `<head ... />` element
+/// inserts CSS as `<style .. />` under `<head ... />` element, or the shadow root where the app is mounted,
+/// class names are registered separately for each app, failures of inserting are returned from rendering of the app
 /// notice that the code only generats once and being cached as DOM states,
 ///
 /// NOT working for dynamic styles that changes over time, use inline styles instead.
@@ -701,43 +696,31 @@ where
   T: Into<String> + Clone,
   U: Into<String> + Clone + Display,
 {
-  let mut defined_styles = CLASS_NAME_IN_TAGS.write().expect("access styles");
-  if defined_styles.contains(&name.to_owned().into()) {
-    name.into()
-  } else {
-    let window = web_sys::window().expect("window");
-    let document = window.document().expect("load document");
-    let head = document.head().expect("head");
-    let style_tag = document.create_element("style").expect("create style tag");
-    style_tag
-      .set_attribute("id", &format!("def__{}", name.to_owned().into()))
-      .expect("name tag");
-
+  let name: String = name.into();
+  let registry = StyleRegistry::current();
+  let declared = registry.declare(&name, || {
     let mut styles = String::from("");
     for (query, properties) in rules {
       styles.push_str(
         &query
           .to_string()
-          .replace("$0", &format!(".{}", &name.to_owned().into()))
-          .replace('&', &format!(".{}", &name.to_owned().into())),
+          .replace("$0", &format!(".{}", &name))
+          .replace('&', &format!(".{}", &name)),
       );
       styles.push_str(" {\n");
       styles.push_str(&properties.to_string());
       styles.push_str("}\n");
     }
-
-    style_tag.dyn_ref::<Element>().expect("into element").set_inner_html(&styles);
-    head
-      .append_child(style_tag.dyn_ref::<Element>().expect("get element"))
-      .expect("add style");
-
-    defined_styles.insert(name.to_owned().into());
-
-    name.into()
+    styles
+  });
+  if let Err(e) = declared {
+    registry.report(e);
   }
+  name
 }
 /// does internal work inside the macro `contained_style!(name, &styles)`.
-/// inserts CSS as `<style .. />` under `<head ... />` element
+/// inserts CSS as `<style .. />` under `<head ... />` element, or the shadow root where the app is mounted,
+/// class names are registered separately for each app, failures of inserting are returned from rendering of the app
 /// notice that the code only generats once and being cached as DOM states, with extra `<contained> { ... }` wrapper
 ///
 /// NOT working for dynamic styles that changes over time, use inline styles instead.
@@ -746,25 +729,16 @@ where
   T: Into<String> + Clone,
   U: Into<String> + Clone + Display,
 {
-  let mut defined_styles = CLASS_NAME_IN_TAGS.write().expect("access styles");
-  if defined_styles.contains(&name.to_owned().into()) {
-    name.into()
-  } else {
-    let window = web_sys::window().expect("window");
-    let document = window.document().expect("load document");
-    let head = document.head().expect("head");
-    let style_tag = document.create_element("style").expect("create style tag");
-    style_tag
-      .set_attribute("id", &format!("def__{}", name.to_owned().into()))
-      .expect("name tag");
-
+  let name: String = name.into();
+  let registry = StyleRegistry::current();
+  let declared = registry.declare(&name, || {
     let mut styles = String::from("");
     for (contained, query, properties) in rules {
       styles.push_str(
         &query
           .to_string()
-          .replace("$0", &format!(".{}", &name.to_owned().into()))
-          .replace('&', &format!(".{}", &name.to_owned().into())),
+          .replace("$0", &format!(".{}", &name))
+          .replace('&', &format!(".{}", &name)),
       );
       styles.push_str(" {\n");
       styles.push_str(&properties.to_string());
@@ -774,16 +748,12 @@ where
         styles = format!("{} {{\n{}\n}}", contained, styles);
       }
     }
-
-    style_tag.dyn_ref::<Element>().expect("into element").set_inner_html(&styles);
-    head
-      .append_child(style_tag.dyn_ref::<Element>().expect("get element"))
-      .expect("add style");
-
-    defined_styles.insert(name.to_owned().into());
-
-    name.into()
+    styles
+  });
+  if let Err(e) = declared {
+    registry.report(e);
   }
+  name
 }
 
 /// turns `src/a/b.rs` into `a_b`, (used inside macro)
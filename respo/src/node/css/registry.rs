@@ -0,0 +1,161 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Node};
+
+use crate::app::util;
+use crate::RespoError;
+
+thread_local! {
+  /// for styles declared when no app is rendering
+  static DEFAULT_REGISTRY: StyleRegistry = StyleRegistry::default();
+  /// registries of apps that are rendering, the last one is current
+  static ENTERED_REGISTRIES: RefCell<Vec<StyleRegistry>> = const { RefCell::new(vec![]) };
+}
+
+/// class names of static styles and their `<style/>` tags inserted into the page.
+///
+/// Each app owns a registry and enters it while calling its view, so apps on one page register styles independently.
+/// Apps rendering into the same container share `<style/>` tags of same class names, instead of inserting tags of duplicated ids.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StyleRegistry(Rc<RefCell<StyleRegistryData>>);
+
+#[derive(Debug, Default)]
+struct StyleRegistryData {
+  names: HashSet<String>,
+  /// styles go into the shadow root containing this node if there's one, otherwise into `<head/>`
+  anchor: Option<Node>,
+  tags: Vec<Element>,
+  /// failure of declaring styles inside `enter`, returned when leaving
+  failure: Option<RespoError>,
+}
+
+impl StyleRegistry {
+  pub fn new(anchor: &Node) -> Self {
+    Self(Rc::new(RefCell::new(StyleRegistryData {
+      anchor: Some(anchor.to_owned()),
+      ..Default::default()
+    })))
+  }
+
+  /// calls `f` with this registry being current, styles declared inside are registered here.
+  /// styles failed to be declared inside are reported as the error of `f`
+  pub fn enter<R>(&self, f: impl FnOnce() -> Result<R, RespoError>) -> Result<R, RespoError> {
+    ENTERED_REGISTRIES.with(|xs| xs.borrow_mut().push(self.to_owned()));
+    let ret = f();
+    ENTERED_REGISTRIES.with(|xs| xs.borrow_mut().pop());
+    let failure = self.0.borrow_mut().failure.take();
+    match failure {
+      Some(e) => ret.and(Err(e)),
+      None => ret,
+    }
+  }
+
+  /// the registry of the app that is rendering, or the default one
+  pub fn current() -> Self {
+    ENTERED_REGISTRIES
+      .with(|xs| xs.borrow().last().cloned())
+      .unwrap_or_else(|| DEFAULT_REGISTRY.with(|r| r.to_owned()))
+  }
+
+  #[cfg(test)]
+  pub fn is_same(&self, other: &Self) -> bool {
+    Rc::ptr_eq(&self.0, &other.0)
+  }
+
+  /// inserts a `<style/>` tag for class `name` unless it's registered, `build_css` is only called for new names
+  pub fn declare(&self, name: &str, build_css: impl FnOnce() -> String) -> Result<(), RespoError> {
+    if self.0.borrow().names.contains(name) {
+      return Ok(());
+    }
+    let styles = build_css();
+
+    let window = web_sys::window().ok_or_else(|| RespoError::Dom(String::from("window not found")))?;
+    let document = window
+      .document()
+      .ok_or_else(|| RespoError::Dom(String::from("document not found")))?;
+
+    let mut data = self.0.borrow_mut();
+    let root = data.anchor.as_ref().map(|anchor| anchor.get_root_node());
+    let container: Node = match root {
+      Some(root) if root.node_type() == Node::DOCUMENT_FRAGMENT_NODE => root,
+      _ => document
+        .head()
+        .ok_or_else(|| RespoError::Dom(String::from("document head not found")))?
+        .unchecked_into(),
+    };
+
+    // another app in the same container might have inserted the tag, it's shared rather than duplicated
+    let id = format!("def__{}", name);
+    let style_tag = match find_style_tag(&container, &id) {
+      Some(tag) => tag,
+      None => {
+        let style_tag = document
+          .create_element("style")
+          .map_err(|e| RespoError::Dom(format!("failed to create style tag: {:?}", e)))?;
+        style_tag
+          .set_attribute("id", &id)
+          .map_err(|e| RespoError::Dom(format!("failed to name style tag: {:?}", e)))?;
+        style_tag.set_inner_html(&styles);
+        container
+          .append_child(&style_tag)
+          .map_err(|e| RespoError::Dom(format!("failed to add style tag: {:?}", e)))?;
+        style_tag
+      }
+    };
+    set_tag_users(&style_tag, tag_users(&style_tag) + 1)?;
+
+    data.names.insert(name.to_owned());
+    data.tags.push(style_tag);
+    Ok(())
+  }
+
+  /// keeps failure of declaring to be returned from `enter`, only the first one is kept.
+  /// there's nobody to return it to when no app is rendering, so it's logged
+  pub fn report(&self, e: RespoError) {
+    let mut data = self.0.borrow_mut();
+    if data.anchor.is_none() {
+      util::error_log!("failed to declare styles: {}", e);
+    } else if data.failure.is_none() {
+      data.failure = Some(e);
+    }
+  }
+
+  /// removes inserted `<style/>` tags, names are registered again when declared later
+  pub fn clear(&self) {
+    let mut data = self.0.borrow_mut();
+    for tag in data.tags.drain(..) {
+      match tag_users(&tag) {
+        0 | 1 => tag.remove(),
+        n => {
+          if let Err(e) = set_tag_users(&tag, n - 1) {
+            util::warn_log!("failed to release style tag: {}", e);
+          }
+        }
+      }
+    }
+    data.names.clear();
+  }
+}
+
+/// counts registries sharing a `<style/>` tag, the tag is removed when the last one clears
+const TAG_USERS: &str = "data-registries";
+
+/// finds a `<style/>` tag inserted with `id` directly under `container`
+fn find_style_tag(container: &Node, id: &str) -> Option<Element> {
+  let children = container.child_nodes();
+  (0..children.length())
+    .filter_map(|idx| children.item(idx))
+    .filter_map(|child| child.dyn_into::<Element>().ok())
+    .find(|el| el.id() == id)
+}
+
+fn tag_users(tag: &Element) -> u32 {
+  tag.get_attribute(TAG_USERS).and_then(|n| n.parse().ok()).unwrap_or(0)
+}
+
+fn set_tag_users(tag: &Element, n: u32) -> Result<(), RespoError> {
+  tag
+    .set_attribute(TAG_USERS, &n.to_string())
+    .map_err(|e| RespoError::Dom(format!("failed to count users of style tag: {:?}", e)))
+}
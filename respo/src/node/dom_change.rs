@@ -8,9 +8,8 @@ use crate::node::{RespoEffectType, RespoIndexKey, RespoNode, StrDict};
 
 use crate::node::str_dict_to_cirrus_dict;
 
-/// DOM operations used for diff/patching
-/// performance is not optimial since looking up the DOM via dom_path has repetitive operations,
-/// might need to fix in future is overhead observed.
+/// DOM operations used for diff/patching,
+/// `dom_path` locates the target among live nodes kept by the renderer, no DOM queries are involved.
 #[derive(Debug, Clone)]
 pub(crate) enum DomChange<T>
 where
@@ -69,6 +68,17 @@ where
       DomChange::Effect { dom_path, .. } => dom_path,
     }
   }
+
+  pub fn get_coord(&self) -> &Vec<RespoCoord> {
+    match self {
+      DomChange::ReplaceElement { coord, .. } => coord,
+      DomChange::ModifyChildren { coord, .. } => coord,
+      DomChange::ModifyAttrs { coord, .. } => coord,
+      DomChange::ModifyStyle { coord, .. } => coord,
+      DomChange::ModifyEvent { coord, .. } => coord,
+      DomChange::Effect { coord, .. } => coord,
+    }
+  }
 }
 
 impl<T> From<DomChange<T>> for Cirru
@@ -163,7 +173,6 @@ where
   }
 }
 
-#[allow(dead_code)]
 pub fn changes_to_cirru<T>(change: &[DomChange<T>]) -> Cirru
 where
   T: Debug + Clone,
@@ -185,6 +194,11 @@ where
   RemoveAt(u32),
   Append(RespoIndexKey, RespoNode<T>),
   Prepend(RespoIndexKey, RespoNode<T>),
+  /// moves an existing child, `to` is its index after moving
+  Move {
+    from: u32,
+    to: u32,
+  },
   /// order is required in operating children elements, so put effect inside
   NestedEffect {
     nested_coord: Vec<RespoCoord>,
@@ -217,6 +231,10 @@ where
         let xs = vec!["::prepend".into(), key.into(), node.into()];
         Cirru::List(xs)
       }
+      ChildDomOp::Move { from, to } => {
+        let xs = vec!["::move".into(), Cirru::from(from.to_string()), Cirru::from(to.to_string())];
+        Cirru::List(xs)
+      }
       ChildDomOp::NestedEffect {
         nested_coord,
         nested_dom_path,
@@ -241,7 +259,7 @@ where
 }
 
 /// coordinate system on RespoNode, to lookup among elements and components
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum RespoCoord {
   Key(RespoIndexKey),
   /// for indexing by component name, even though there's only one of that
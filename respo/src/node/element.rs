@@ -5,7 +5,7 @@ use std::{
   rc::Rc,
 };
 
-use crate::{css::RespoStyle, DispatchFn, RespoEvent, RespoIndexKey, RespoListenerFn, RespoNode};
+use crate::{css::RespoStyle, DispatchFn, RespoError, RespoEvent, RespoEventOptions, RespoIndexKey, RespoListenerFn, RespoNode};
 
 use super::css::respo_style;
 
@@ -118,45 +118,52 @@ where
   }
   pub fn on_click<U>(self, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     self.on_named_event("click", handler)
   }
   pub fn on_input<U>(self, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     self.on_named_event("input", handler)
   }
   /// handle keydown event
   pub fn on_keydown<U>(self, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     self.on_named_event("keydown", handler)
   }
   /// handle focus event
   pub fn on_focus<U>(self, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     self.on_named_event("focus", handler)
   }
   /// handle change event
   pub fn on_change<U>(self, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     self.on_named_event("change", handler)
   }
-  /// attach a listener by event name(only a small set of events are supported)
+  /// attach a listener by event name, events without a dedicated variant of `RespoEvent` arrive as `RespoEvent::Generic`
   pub fn on_named_event<U>(self, name: &str, handler: U) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
+  {
+    self.on_named_event_with(name, RespoEventOptions::default(), handler)
+  }
+  /// attach a listener by event name, with `options` applied to the DOM event before the handler is called
+  pub fn on_named_event_with<U>(self, name: &str, options: RespoEventOptions, handler: U) -> Self
+  where
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     let el = self;
     let mut e = el.event.to_owned();
-    e.insert(name.into(), RespoListenerFn::new(handler));
+    e.insert(name.into(), RespoListenerFn::new(handler, options));
     RespoElement { event: e, ..el }
   }
   /// add children elements,
@@ -0,0 +1,169 @@
+//! Serialize virtual DOM into HTML strings, no browser APIs involved,
+//! so it also runs on native targets for server-side rendering.
+
+use std::fmt::{Debug, Write};
+
+use crate::node::css::RespoStyle;
+use crate::node::{RespoComponent, RespoElement, RespoNode};
+use crate::RespoError;
+
+/// elements that have no closing tags in HTML
+const VOID_ELEMENTS: [&str; 14] = [
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// renders a tree into HTML, attributes are handled in the same way as `build_dom_tree` does in browser:
+///
+/// - `innerText` and `value` of `<textarea/>` are escaped as text content,
+/// - `innerHTML` is written as it is,
+/// - `htmlFor` is written as `for`, inline styles are written into `style`.
+///
+/// Event listeners and effects are not included since they only exist in browser,
+/// call `app.hydrate_loop()` in browser to attach them to the rendered HTML.
+/// Fails when a memo fails to render outside of boundaries, rather than leaving out a subtree that hydrating would not match.
+///
+/// ```rust
+/// use respo::{div, span, RespoNode};
+/// use respo::css::respo_style;
+///
+/// let tree: RespoNode<()> = div()
+///   .class("app")
+///   .style(respo_style().padding(4))
+///   .children([span().inner_text("a < b").to_node()])
+///   .to_node();
+/// assert_eq!(
+///   respo::render_to_html(&tree).unwrap(),
+///   r#"<div class="app" style="padding: 4px;"><span>a &lt; b</span></div>"#
+/// );
+/// ```
+pub fn render_to_html<T>(tree: &RespoNode<T>) -> Result<String, RespoError>
+where
+  T: Debug + Clone,
+{
+  let mut buffer = String::new();
+  write_node(tree, &mut buffer)?;
+  Ok(buffer)
+}
+
+fn write_node<T>(tree: &RespoNode<T>, buffer: &mut String) -> Result<(), RespoError>
+where
+  T: Debug + Clone,
+{
+  match tree {
+    RespoNode::Component(RespoComponent {
+      name,
+      tree,
+      boundary: Some(boundary),
+      ..
+    }) => {
+      // output of a failed subtree is dropped, like the renderer does
+      let mut inner = String::new();
+      match write_node(tree, &mut inner) {
+        Ok(()) => buffer.push_str(&inner),
+        Err(e) => write_node(&boundary.catch(name, &e), buffer)?,
+      }
+      Ok(())
+    }
+    RespoNode::Component(RespoComponent { tree, .. }) => write_node(tree, buffer),
+    RespoNode::Element(element) => write_element(element, buffer),
+    RespoNode::Referenced(cell) => write_node(cell, buffer),
+    RespoNode::Memo(memo) => write_node(&memo.render()?, buffer),
+  }
+}
+
+fn write_element<T>(element: &RespoElement<T>, buffer: &mut String) -> Result<(), RespoError>
+where
+  T: Debug + Clone,
+{
+  let RespoElement {
+    name,
+    attributes,
+    style,
+    children,
+    ..
+  } = element;
+  let tag = name.as_ref();
+
+  // sort attributes to keep output stable
+  let mut keys = attributes.keys().collect::<Vec<_>>();
+  keys.sort();
+
+  let mut inner = String::new();
+  buffer.push('<');
+  buffer.push_str(tag);
+  for key in keys {
+    let value = &attributes[key];
+    match key.as_ref() {
+      // style is handled outside attrs
+      "style" => {}
+      "innerText" => inner.push_str(&escape_text(value).replace('\n', "<br>")),
+      "innerHTML" => inner.push_str(value),
+      "value" if tag == "textarea" => inner.push_str(&escape_text(value)),
+      "htmlFor" => write_attribute(buffer, "for", value),
+      k => write_attribute(buffer, k, value),
+    }
+  }
+  if !style.is_empty() {
+    write_attribute(buffer, "style", &inline_style(style));
+  }
+  buffer.push('>');
+
+  if VOID_ELEMENTS.contains(&tag) {
+    return Ok(());
+  }
+
+  buffer.push_str(&inner);
+  for (_k, child) in children {
+    write_node(child, buffer)?;
+  }
+  buffer.push_str("</");
+  buffer.push_str(tag);
+  buffer.push('>');
+  Ok(())
+}
+
+/// inline styles in a single line, used in `style` attribute
+pub(crate) fn inline_style(style: &RespoStyle) -> String {
+  let mut rules = String::new();
+  for (idx, (property, value)) in style.0.iter().enumerate() {
+    if idx > 0 {
+      rules.push(' ');
+    }
+    let _ = write!(rules, "{}: {};", property, value);
+  }
+  rules
+}
+
+fn write_attribute(buffer: &mut String, key: &str, value: &str) {
+  let _ = write!(buffer, " {}=\"{}\"", key, escape_attribute(value));
+}
+
+/// escapes text content between tags
+fn escape_text(s: &str) -> String {
+  let mut result = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => result.push_str("&amp;"),
+      '<' => result.push_str("&lt;"),
+      '>' => result.push_str("&gt;"),
+      _ => result.push(c),
+    }
+  }
+  result
+}
+
+/// escapes text inside double quoted attribute values
+fn escape_attribute(s: &str) -> String {
+  let mut result = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => result.push_str("&amp;"),
+      '<' => result.push_str("&lt;"),
+      '>' => result.push_str("&gt;"),
+      '"' => result.push_str("&quot;"),
+      '\'' => result.push_str("&#39;"),
+      _ => result.push(c),
+    }
+  }
+  result
+}
@@ -1,22 +1,27 @@
 use std::{fmt::Debug, rc::Rc};
 
-use web_sys::{FocusEvent, InputEvent, KeyboardEvent, MouseEvent};
+use web_sys::{Event, FocusEvent, InputEvent, KeyboardEvent, MouseEvent};
 
 use crate::node::{DispatchFn, RespoCoord};
+use crate::RespoError;
 
 /// (internal) struct to store event handler function on the tree
 #[derive(Clone)]
-pub(crate) struct RespoListenerFn<T>(Rc<dyn Fn(RespoEvent, DispatchFn<T>) -> Result<(), String>>)
+pub(crate) struct RespoListenerFn<T>
 where
-  T: Debug + Clone;
+  T: Debug + Clone,
+{
+  handler: Rc<dyn Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError>>,
+  pub options: RespoEventOptions,
+}
 
 impl<T> PartialEq for RespoListenerFn<T>
 where
   T: Debug + Clone,
 {
-  /// returns true since informations are erased when attaching to the DOM
-  fn eq(&self, _: &Self) -> bool {
-    true
+  /// handlers are treated as equal since informations are erased when attaching to the DOM, only options are compared
+  fn eq(&self, other: &Self) -> bool {
+    self.options == other.options
   }
 }
 
@@ -27,7 +32,7 @@ where
   T: Debug + Clone,
 {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "RespoEventHandler(...)")
+    write!(f, "RespoEventHandler(... {:?})", self.options)
   }
 }
 
@@ -35,14 +40,58 @@ impl<T> RespoListenerFn<T>
 where
   T: Debug + Clone,
 {
-  pub fn new<U>(handler: U) -> Self
+  pub fn new<U>(handler: U, options: RespoEventOptions) -> Self
   where
-    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), String> + 'static,
+    U: Fn(RespoEvent, DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
-    Self(Rc::new(handler))
+    Self {
+      handler: Rc::new(handler),
+      options,
+    }
   }
-  pub fn run(&self, event: RespoEvent, dispatch: DispatchFn<T>) -> Result<(), String> {
-    (self.0)(event, dispatch)
+  pub fn run(&self, event: RespoEvent, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
+    (self.handler)(event, dispatch)
+  }
+}
+
+/// declares what to do with a DOM event before it's passed to the handler,
+/// applied synchronously by the backend while the browser is still dispatching the event.
+///
+/// ```rust
+/// use respo::{textarea, RespoEventOptions};
+///
+/// // keeps focus in textarea when Tab is pressed
+/// let el = textarea::<()>().on_named_event_with("keydown", RespoEventOptions::new().prevent_default(), |_e, _dispatch| Ok(()));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RespoEventOptions {
+  prevent_default: bool,
+  stop_propagation: bool,
+}
+
+impl RespoEventOptions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// calls `.preventDefault()` on the DOM event
+  pub fn prevent_default(self) -> Self {
+    Self {
+      prevent_default: true,
+      ..self
+    }
+  }
+  /// calls `.stopPropagation()` on the DOM event, listeners on ancestors are not called
+  pub fn stop_propagation(self) -> Self {
+    Self {
+      stop_propagation: true,
+      ..self
+    }
+  }
+  pub fn prevents_default(&self) -> bool {
+    self.prevent_default
+  }
+  pub fn stops_propagation(&self) -> bool {
+    self.stop_propagation
   }
 }
 
@@ -54,8 +103,8 @@ pub struct RespoEventMark {
   pub coord: Vec<RespoCoord>,
   /// TODO event type
   pub name: String,
-  /// partial copy of DOM events, that shares across threads,
-  /// but being async, methods like `.prevent_default()` and `.stop_propagation()` will not work
+  /// partial copy of DOM events. Handlers are called while the browser dispatches the event,
+  /// so `.prevent_default()` and `.stop_propagation()` still work, see also `RespoEventOptions`
   pub event_info: RespoEvent,
 }
 
@@ -93,4 +142,10 @@ pub enum RespoEvent {
   },
   Focus(FocusEvent),
   Blur(FocusEvent),
+  /// events without a dedicated variant, like `mouseenter`, `wheel`, `pointerdown`, `touchstart`, `dragover`, `submit` or `paste`,
+  /// fields are read from `original_event` after casting with `dyn_ref`, e.g. into `web_sys::WheelEvent`
+  Generic {
+    name: String,
+    original_event: Event,
+  },
 }
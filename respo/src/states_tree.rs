@@ -1,9 +1,15 @@
 //! Respo does not provide local states in components, but a global states tree.
 //! `RespoStatesTree` tree has concept of "cursor", which is a path to the current state in the tree.
 //! use `branch.pick(name)` to get a child branch, and `branch.set_in_mut(change)` to update the tree.
+//! `StateCursor` reads a branch as a typed value and builds actions for updating it.
+//! Branches not picked in last render can be dropped with `sweep`, see `RespoApp::prune_states`.
 
+mod cursor;
 mod dyn_eq;
+mod picks;
 mod state;
+#[cfg(test)]
+mod tests;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -12,9 +18,12 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::rc::Rc;
 
-use crate::warn_log;
+use crate::{warn_log, RespoError};
 pub(crate) use dyn_eq::DynEq;
 
+pub use cursor::StateCursor;
+pub use picks::RespoStatesPicks;
+pub(crate) use picks::{note_caught_error, record_picks, replay_picks};
 pub use state::RespoState;
 
 // use wasm_bindgen::JsValue;
@@ -22,18 +31,51 @@ pub use state::RespoState;
 /// Respo maintains states in a tree structure, where the keys are strings,
 /// each child component "picks" a key to attach its own state to the tree,
 /// and it dispatches events to global store to update the state.
+///
+/// Branches are shared with `Rc`, so `pick` does not copy subtrees,
+/// and updating copies only nodes along the path being modified.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RespoStatesTree {
   /// local data
   #[serde(skip)]
   pub data: Option<RespoStateBranch>,
-  pub backup: Option<Value>,
+  pub backup: Option<Rc<Value>>,
   /// the path to the current state in the tree, use in updating
-  pub cursor: Vec<Rc<str>>,
+  #[serde(skip)]
+  cursor: StatesPath,
   // pub data_type_name: Option<TypeId>,
   // pub data_revision: usize,
-  /// holding children states
-  pub branches: BTreeMap<Rc<str>, Box<RespoStatesTree>>,
+  /// holding children states, a child failed to load from backup is dropped without failing others,
+  /// while backups in wrong shapes for their states are checked later in `try_cast_branch`
+  #[serde(default, deserialize_with = "deserialize_branches")]
+  pub branches: Rc<BTreeMap<Rc<str>, RespoStatesTree>>,
+  /// count of updates with `set_in_mut`, only tracked on the tree where updates happen
+  #[serde(skip)]
+  revision: u64,
+  /// picked with `pick_persistent` once, not dropped by `sweep`
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub persistent: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+  !*b
+}
+
+fn deserialize_branches<'de, D>(deserializer: D) -> Result<Rc<BTreeMap<Rc<str>, RespoStatesTree>>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let raw = BTreeMap::<Rc<str>, Value>::deserialize(deserializer)?;
+  let mut branches = BTreeMap::new();
+  for (name, v) in raw {
+    match serde_json::from_value::<RespoStatesTree>(v) {
+      Ok(branch) => {
+        branches.insert(name, branch);
+      }
+      Err(e) => warn_log!("dropped states branch {} failed to load: {}", name, e),
+    }
+  }
+  Ok(Rc::new(branches))
 }
 
 impl Hash for RespoStatesTree {
@@ -57,7 +99,7 @@ impl Eq for RespoStatesTree {}
 impl RespoStatesTree {
   /// get cursor
   pub fn path(&self) -> Vec<Rc<str>> {
-    self.cursor.to_owned()
+    self.cursor.to_vec()
   }
 
   /// get shared data from state tree. fallback to backup and then default
@@ -78,6 +120,8 @@ impl RespoStatesTree {
         let mut t = T::default();
         if let Err(e) = t.restore_from(v) {
           warn_log!("failed to restore from backup: {} , at {:?}", e, self.cursor);
+          // partially restored state is not trusted
+          return Rc::new(T::default());
         }
         Rc::new(t)
       }
@@ -85,60 +129,208 @@ impl RespoStatesTree {
     }
   }
 
-  /// pick a child branch as new cursor
+  /// like `cast_branch`, but returns an error when the state is of another type.
+  /// Backups are only checked when they are read, a backup that fails to restore into `T`,
+  /// like one saved by an older version of the app, is dropped and the state is reset to default
+  pub fn try_cast_branch<T>(&self) -> Result<Rc<T>, RespoError>
+  where
+    T: Clone + Default + RespoState + 'static,
+  {
+    if let Some(v) = &self.data {
+      return match v.0.as_ref().as_any().downcast_ref::<T>() {
+        Some(v) => Ok(Rc::new(v.to_owned())),
+        None => Err(RespoError::StateMismatch(format!(
+          "expected {} at {:?}, got {:?}",
+          std::any::type_name::<T>(),
+          self.cursor,
+          v.0
+        ))),
+      };
+    }
+
+    let mut t = T::default();
+    if let Some(v) = &self.backup {
+      if let Err(e) = t.restore_from(v) {
+        warn_log!("reset state at {:?}, failed to restore from backup: {}", self.cursor, e);
+        // partially restored state is not trusted, the backup is replaced in next update
+        return Ok(Rc::new(T::default()));
+      }
+    }
+    Ok(Rc::new(t))
+  }
+
+  /// pick a child branch and read it as a typed cursor, see `StateCursor`
+  pub fn pick_cursor<T>(&self, name: &str) -> Result<StateCursor<T>, RespoError>
+  where
+    T: Debug + Clone + Default + PartialEq + RespoState + 'static,
+  {
+    StateCursor::new(&self.pick(name))
+  }
+
+  /// pick a child branch as new cursor, children of the branch are shared rather than copied
   pub fn pick(&self, name: &str) -> RespoStatesTree {
-    let mut next_cursor = self.cursor.to_owned();
-    next_cursor.push(Rc::from(name));
+    self.pick_as(name, false)
+  }
 
-    if self.branches.contains_key(name) {
-      let prev = &self.branches[name];
-      Self {
+  /// like `pick`, and marks the branch to be kept by `sweep` even when it's no longer picked,
+  /// for states like drafts that should survive after the component is removed
+  pub fn pick_persistent(&self, name: &str) -> RespoStatesTree {
+    self.pick_as(name, true)
+  }
+
+  fn pick_as(&self, name: &str, persistent: bool) -> RespoStatesTree {
+    let cursor = self.cursor.child(name);
+    if picks::is_recording() {
+      picks::note_pick(&cursor.to_vec(), persistent);
+    }
+    match self.branches.get(name) {
+      Some(prev) => Self {
         data: prev.data.to_owned(),
         backup: prev.backup.to_owned(),
-        // data_revision: prev.data_revision,
-        // data_type_name: prev.data_type_name.to_owned(),
-        cursor: next_cursor,
+        cursor,
         branches: prev.branches.to_owned(),
+        revision: prev.revision,
+        persistent: prev.persistent || persistent,
+      },
+      None => Self {
+        cursor,
+        persistent,
+        ..Self::default()
+      },
+    }
+  }
+
+  /// drops branches that are not in `picks` and not persistent, returns count of dropped branches.
+  /// Branches picked with `pick_persistent` are marked as persistent, along with their children.
+  /// Nothing is copied when there's nothing to drop or mark.
+  /// Nothing is dropped when a boundary caught an error in the render, since states of the failed part are not picked
+  pub fn sweep(&mut self, picks: &RespoStatesPicks) -> usize {
+    if picks.caught_error || !self.needs_sweep(picks) {
+      return 0;
+    }
+    let branches = Rc::make_mut(&mut self.branches);
+    let mut count = 0;
+    branches.retain(|name, branch| {
+      let kept = branch.persistent || picks.branches.contains_key(name);
+      if !kept {
+        count += 1;
+      }
+      kept
+    });
+    for (name, branch) in branches.iter_mut() {
+      if branch.persistent {
+        continue;
       }
-    } else {
-      Self {
-        data: None,
-        backup: None,
-        cursor: next_cursor,
-        // data_type_name: None,
-        // data_revision: 0,
-        branches: BTreeMap::new(),
+      if let Some(p) = picks.branches.get(name) {
+        if p.persistent {
+          branch.persistent = true;
+        } else {
+          count += branch.sweep(p);
+        }
       }
     }
+    count
   }
 
-  /// in-place mutation of state tree
+  fn needs_sweep(&self, picks: &RespoStatesPicks) -> bool {
+    self.branches.iter().any(|(name, branch)| {
+      !branch.persistent
+        && match picks.branches.get(name) {
+          None => true,
+          Some(p) => p.persistent || branch.needs_sweep(p),
+        }
+    })
+  }
+
+  /// changes whenever the tree is updated with `RespoStore::update_states`
+  pub fn revision(&self) -> u64 {
+    self.revision
+  }
+
+  /// in-place mutation of state tree, branches shared with other trees are copied before being modified
   pub(crate) fn set_in_mut(&mut self, change: RespoUpdateState) {
-    if change.cursor.is_empty() {
-      change.data.clone_into(&mut self.data);
-      change.backup.clone_into(&mut self.backup);
-      // self.data_type_name = new_state.0.as_ref().map(|v| v.type_id().to_owned());
-      // self.data_revision += 1;
-    } else {
-      let (p_head, p_rest) = change.cursor.split_at(1);
-      let p0 = &p_head[0];
-      if let Some(branch) = self.branches.get_mut(p0) {
-        branch.set_in_mut(RespoUpdateState {
-          cursor: p_rest.to_vec(),
-          ..change
-        });
-      } else {
-        let mut branch = self.pick(p0);
-        branch.set_in_mut(RespoUpdateState {
-          cursor: p_rest.to_vec(),
-          ..change
-        });
-        self.branches.insert(p0.to_owned(), Box::new(branch));
+    self.revision += 1;
+    self.set_at(&change.cursor, change.data, change.backup);
+  }
+
+  fn set_at(&mut self, path: &[Rc<str>], data: Option<RespoStateBranch>, backup: Option<Value>) {
+    match path.split_first() {
+      None => {
+        self.data = data;
+        self.backup = backup.map(Rc::new);
+        // self.data_type_name = new_state.0.as_ref().map(|v| v.type_id().to_owned());
+        // self.data_revision += 1;
       }
+      Some((p0, rest)) => {
+        let branches = Rc::make_mut(&mut self.branches);
+        if !branches.contains_key(p0) {
+          let branch = Self {
+            cursor: self.cursor.child(p0),
+            ..Self::default()
+          };
+          branches.insert(p0.to_owned(), branch);
+        }
+        if let Some(branch) = branches.get_mut(p0) {
+          branch.set_at(rest, data, backup);
+        }
+      }
+    }
+  }
+}
+
+/// path of a branch linked to the path of its parent, so picking a child does not copy it
+#[derive(Clone, Default)]
+struct StatesPath(Option<Rc<StatesPathNode>>);
+
+struct StatesPathNode {
+  parent: StatesPath,
+  name: Rc<str>,
+}
+
+impl StatesPath {
+  fn child(&self, name: &str) -> Self {
+    Self(Some(Rc::new(StatesPathNode {
+      parent: self.to_owned(),
+      name: Rc::from(name),
+    })))
+  }
+
+  fn to_vec(&self) -> Vec<Rc<str>> {
+    let mut xs = vec![];
+    let mut current = &self.0;
+    while let Some(node) = current {
+      xs.push(node.name.to_owned());
+      current = &node.parent.0;
+    }
+    xs.reverse();
+    xs
+  }
+}
+
+impl PartialEq for StatesPath {
+  fn eq(&self, other: &Self) -> bool {
+    match (&self.0, &other.0) {
+      (None, None) => true,
+      (Some(a), Some(b)) => Rc::ptr_eq(a, b) || (a.name == b.name && a.parent == b.parent),
+      _ => false,
     }
   }
 }
 
+impl Eq for StatesPath {}
+
+impl Hash for StatesPath {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    self.to_vec().hash(state);
+  }
+}
+
+impl Debug for StatesPath {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_list().entries(self.to_vec()).finish()
+  }
+}
+
 #[derive(Debug, Clone)]
 /// local state in component could be `None` according to the tree structure
 pub struct RespoStateBranch(pub Rc<dyn DynEq>);
@@ -0,0 +1,94 @@
+use std::{fmt::Debug, ops::Deref, rc::Rc};
+
+use crate::{RespoAction, RespoError};
+
+use super::{RespoState, RespoStateBranch, RespoStatesTree};
+
+/// typed handle to a branch of states tree, it holds the value read from the branch along with the path,
+/// and builds actions that update the same branch, so the path can't be used with a state of another type.
+///
+/// ```rust
+/// use respo::states_tree::{RespoStatesTree, RespoUpdateState, StateCursor};
+/// use respo::{RespoAction, RespoError};
+///
+/// #[derive(Debug, Clone)]
+/// enum Op { States(RespoUpdateState) }
+///
+/// impl RespoAction for Op {
+///   type Intent = ();
+///   fn states_action(a: RespoUpdateState) -> Self { Op::States(a) }
+/// }
+///
+/// let states = RespoStatesTree::default();
+/// let cursor: StateCursor<bool> = states.pick_cursor("expanded")?;
+/// assert!(!*cursor);
+///
+/// let Op::States(change) = cursor.update(|v| *v = !*v);
+/// assert_eq!(change.cursor, cursor.path());
+/// assert_eq!(change.backup, Some(serde_json::json!(true)));
+/// # Ok::<(), RespoError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct StateCursor<S> {
+  path: Vec<Rc<str>>,
+  value: Rc<S>,
+}
+
+impl<S> StateCursor<S>
+where
+  S: Debug + Clone + Default + PartialEq + RespoState + 'static,
+{
+  /// reads the state at the branch, it's `S::default()` when the branch is empty or the backup fails to restore,
+  /// returns an error when the branch holds a state of another type
+  pub fn new(states: &RespoStatesTree) -> Result<Self, RespoError> {
+    Ok(Self {
+      path: states.path(),
+      value: states.try_cast_branch::<S>()?,
+    })
+  }
+
+  /// path of the branch
+  pub fn path(&self) -> &[Rc<str>] {
+    &self.path
+  }
+
+  /// shared value of the state
+  pub fn value(&self) -> Rc<S> {
+    self.value.to_owned()
+  }
+
+  /// action that replaces the state with `value`
+  pub fn set<T>(&self, value: S) -> T
+  where
+    T: RespoAction,
+  {
+    T::build_states_action(&self.path, Some(RespoStateBranch::new(Rc::new(value))))
+  }
+
+  /// action that replaces the state with a modified copy of current value
+  pub fn update<T, F>(&self, f: F) -> T
+  where
+    T: RespoAction,
+    F: FnOnce(&mut S),
+  {
+    let mut value = (*self.value).to_owned();
+    f(&mut value);
+    self.set(value)
+  }
+
+  /// action that empties the branch, later reads get `S::default()`
+  pub fn reset<T>(&self) -> T
+  where
+    T: RespoAction,
+  {
+    T::build_states_action(&self.path, None)
+  }
+}
+
+impl<S> Deref for StateCursor<S> {
+  type Target = S;
+
+  fn deref(&self) -> &S {
+    &self.value
+  }
+}
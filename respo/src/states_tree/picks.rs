@@ -0,0 +1,87 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+thread_local! {
+  /// picks being recorded, the last one is current, picks are merged into outer ones when finished
+  static RECORDING_PICKS: RefCell<Vec<RespoStatesPicks>> = const { RefCell::new(vec![]) };
+}
+
+/// paths picked from states tree during a render, in the shape of the tree.
+/// Used by `RespoStatesTree::sweep` to drop branches that are no longer rendered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RespoStatesPicks {
+  /// picked with `pick_persistent`, the branch is kept from now on
+  pub persistent: bool,
+  pub branches: BTreeMap<Rc<str>, RespoStatesPicks>,
+  /// a boundary caught an error in the render, states of the failed part are not picked,
+  /// so `sweep` drops nothing until the part renders again
+  pub caught_error: bool,
+}
+
+impl RespoStatesPicks {
+  pub fn is_empty(&self) -> bool {
+    !self.persistent && self.branches.is_empty()
+  }
+
+  /// adds a picked path
+  pub fn insert(&mut self, path: &[Rc<str>], persistent: bool) {
+    let mut target = self;
+    for name in path {
+      target = target.branches.entry(name.to_owned()).or_default();
+    }
+    target.persistent = target.persistent || persistent;
+  }
+
+  /// adds paths picked in `other`
+  pub fn merge(&mut self, other: &RespoStatesPicks) {
+    self.persistent = self.persistent || other.persistent;
+    self.caught_error = self.caught_error || other.caught_error;
+    for (name, branch) in &other.branches {
+      self.branches.entry(name.to_owned()).or_default().merge(branch);
+    }
+  }
+}
+
+/// calls `f` and returns paths picked during the call, paths are also recorded by outer calls
+pub(crate) fn record_picks<R>(f: impl FnOnce() -> R) -> (R, RespoStatesPicks) {
+  RECORDING_PICKS.with(|xs| xs.borrow_mut().push(RespoStatesPicks::default()));
+  let ret = f();
+  let picks = RECORDING_PICKS.with(|xs| {
+    let mut xs = xs.borrow_mut();
+    let picks = xs.pop().unwrap_or_default();
+    if let Some(outer) = xs.last_mut() {
+      outer.merge(&picks);
+    }
+    picks
+  });
+  (ret, picks)
+}
+
+/// records paths picked earlier, for subtrees that are reused without rendering again, like memos
+pub(crate) fn replay_picks(picks: &RespoStatesPicks) {
+  RECORDING_PICKS.with(|xs| {
+    if let Some(current) = xs.borrow_mut().last_mut() {
+      current.merge(picks);
+    }
+  });
+}
+
+pub(super) fn is_recording() -> bool {
+  RECORDING_PICKS.with(|xs| !xs.borrow().is_empty())
+}
+
+pub(super) fn note_pick(path: &[Rc<str>], persistent: bool) {
+  RECORDING_PICKS.with(|xs| {
+    if let Some(current) = xs.borrow_mut().last_mut() {
+      current.insert(path, persistent);
+    }
+  });
+}
+
+/// called by boundaries that render fallbacks, see `RespoStatesPicks::caught_error`
+pub(crate) fn note_caught_error() {
+  RECORDING_PICKS.with(|xs| {
+    if let Some(current) = xs.borrow_mut().last_mut() {
+      current.caught_error = true;
+    }
+  });
+}
@@ -1,3 +1,4 @@
+use crate::RespoError;
 use serde_json::Value;
 
 /// component level state that could be backuped
@@ -5,7 +6,7 @@ pub trait RespoState {
   fn backup(&self) -> Option<Value> {
     None
   }
-  fn restore_from(&mut self, _s: &Value) -> Result<(), String> {
+  fn restore_from(&mut self, _s: &Value) -> Result<(), RespoError> {
     Ok(())
   }
 }
@@ -15,8 +16,10 @@ impl RespoState for bool {
     Some(Value::Bool(*self))
   }
 
-  fn restore_from(&mut self, s: &Value) -> Result<(), String> {
-    *self = s.as_bool().ok_or_else(|| "Expected a boolean value".to_string())?;
+  fn restore_from(&mut self, s: &Value) -> Result<(), RespoError> {
+    *self = s
+      .as_bool()
+      .ok_or_else(|| RespoError::StateRestore("Expected a boolean value".to_owned()))?;
     Ok(())
   }
 }
@@ -26,7 +29,7 @@ impl RespoState for () {
     None
   }
 
-  fn restore_from(&mut self, _s: &Value) -> Result<(), String> {
+  fn restore_from(&mut self, _s: &Value) -> Result<(), RespoError> {
     Ok(())
   }
 }
@@ -0,0 +1,165 @@
+//! tests on updating, picking and sweeping states tree
+
+use std::rc::Rc;
+
+use crate::app::memo::{resolve_memos, MemoCache};
+use crate::node::{RespoComponent, RespoNode};
+use crate::{div, RespoAction, RespoError};
+
+use super::{record_picks, RespoStateBranch, RespoStatesTree, RespoUpdateState, StateCursor};
+
+fn backup_at(path: &[&str], value: i32) -> RespoUpdateState {
+  RespoUpdateState {
+    cursor: path.iter().map(|p| Rc::from(*p)).collect(),
+    data: None,
+    backup: Some(serde_json::json!(value)),
+  }
+}
+
+#[test]
+fn states_tree_shares_branches_not_on_updated_path() {
+  let mut tree = RespoStatesTree::default();
+  tree.set_in_mut(backup_at(&["a", "x"], 1));
+  tree.set_in_mut(backup_at(&["b"], 2));
+  let prev = tree.to_owned();
+
+  tree.set_in_mut(backup_at(&["b"], 3));
+  assert!(Rc::ptr_eq(&tree.branches["a"].branches, &prev.branches["a"].branches));
+  assert!(!Rc::ptr_eq(&tree.branches, &prev.branches));
+  assert_eq!(prev.pick("b").backup.as_deref(), Some(&serde_json::json!(2)));
+  assert_eq!(tree.pick("b").backup.as_deref(), Some(&serde_json::json!(3)));
+
+  let picked = tree.pick("a").pick("x");
+  assert_eq!(picked.path(), [Rc::from("a"), Rc::from("x")]);
+  assert_eq!(picked.backup.as_deref(), Some(&serde_json::json!(1)));
+  assert!(Rc::ptr_eq(&tree.pick("a").branches, &tree.branches["a"].branches));
+}
+
+#[test]
+fn state_cursor_detects_type_mismatch() {
+  let mut tree = RespoStatesTree::default();
+  tree.set_in_mut(RespoUpdateState {
+    cursor: vec![Rc::from("flag")],
+    data: Some(RespoStateBranch::new(Rc::new(true))),
+    backup: Some(serde_json::json!(true)),
+  });
+
+  let cursor = tree.pick_cursor::<bool>("flag").expect("read bool");
+  assert!(*cursor);
+  assert!(matches!(
+    StateCursor::<()>::new(&tree.pick("flag")),
+    Err(RespoError::StateMismatch(_))
+  ));
+}
+
+#[test]
+fn sweep_drops_branches_not_picked() {
+  let mut tree = RespoStatesTree::default();
+  for path in [&["a", "x"][..], &["a", "y"], &["b"], &["draft"]] {
+    tree.set_in_mut(backup_at(path, 1));
+  }
+
+  let ((), picks) = record_picks(|| {
+    tree.pick("a").pick("x");
+    tree.pick_persistent("draft");
+  });
+  assert_eq!(tree.sweep(&picks), 2);
+  assert_eq!(tree.branches.keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["a", "draft"]);
+  assert_eq!(tree.branches["a"].branches.keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["x"]);
+
+  // persistent branch is kept after it's no longer picked, and nothing is copied when nothing changes
+  let ((), picks) = record_picks(|| {
+    tree.pick("a").pick("x");
+  });
+  let prev = tree.branches.to_owned();
+  assert_eq!(tree.sweep(&picks), 0);
+  assert!(Rc::ptr_eq(&prev, &tree.branches));
+}
+
+#[test]
+fn sweep_keeps_states_of_failed_boundary() {
+  let mut tree = RespoStatesTree::default();
+  for path in [&["panel", "draft"][..], &["other"]] {
+    tree.set_in_mut(backup_at(path, 1));
+  }
+  let render = |tree: &RespoStatesTree, broken: bool| {
+    let states = tree.pick("panel");
+    RespoComponent::<()>::boundary(
+      "panel",
+      move || {
+        if broken {
+          return Err(RespoError::Custom("broken".to_owned()));
+        }
+        states.pick("draft");
+        Ok(div().to_node())
+      },
+      |_| div().to_node(),
+    )
+    .on_error(|_| {})
+    .to_node()
+  };
+
+  let (_, picks) = record_picks(|| render(&tree, true));
+  assert!(picks.caught_error);
+  assert_eq!(tree.sweep(&picks), 0);
+  assert!(tree.pick("panel").branches.contains_key("draft"));
+
+  let (_, picks) = record_picks(|| render(&tree, false));
+  assert_eq!(tree.sweep(&picks), 1);
+  assert_eq!(tree.branches.keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["panel"]);
+  assert!(tree.pick("panel").branches.contains_key("draft"));
+}
+
+#[test]
+fn memo_reused_keeps_its_picks() {
+  let states = RespoStatesTree::default();
+  let memo = |states: &RespoStatesTree| -> RespoNode<()> {
+    let states = states.to_owned();
+    div()
+      .children([RespoComponent::memo("m", 1, move || {
+        states.pick("inside");
+        Ok(div().to_node())
+      })])
+      .to_node()
+  };
+  let ((_, mut cache), picks) = record_picks(|| resolve_memos(memo(&states), &mut MemoCache::new()).expect("resolve"));
+  assert!(picks.branches.contains_key("inside"));
+  let (_, picks) = record_picks(|| resolve_memos(memo(&states), &mut cache).expect("resolve"));
+  assert!(picks.branches.contains_key("inside"));
+}
+
+#[test]
+fn broken_states_branch_is_dropped_alone() {
+  let tree: RespoStatesTree = serde_json::from_str(
+    r#"{"backup": null, "branches": {"good": {"backup": 1, "branches": {}}, "bad": {"backup": 2, "branches": 3}}}"#,
+  )
+  .expect("load tree");
+  assert_eq!(tree.branches.keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["good"]);
+  assert_eq!(tree.pick("good").backup.as_deref(), Some(&serde_json::json!(1)));
+}
+
+#[derive(Debug, Clone)]
+struct StatesOp(RespoUpdateState);
+
+impl RespoAction for StatesOp {
+  type Intent = ();
+  fn states_action(a: RespoUpdateState) -> Self {
+    Self(a)
+  }
+}
+
+#[test]
+fn backup_in_wrong_shape_is_reset_when_read() {
+  let tree: RespoStatesTree =
+    serde_json::from_str(r#"{"backup": null, "branches": {"flag": {"backup": "yes", "branches": {}}}}"#).expect("load tree");
+  assert!(tree.branches.contains_key("flag"), "shapes of backups are not checked in loading");
+
+  let cursor = tree.pick_cursor::<bool>("flag").expect("read bool");
+  assert!(!*cursor, "reset to default");
+
+  let StatesOp(change) = cursor.set(true);
+  let mut tree = tree;
+  tree.set_in_mut(change);
+  assert_eq!(tree.pick("flag").backup.as_deref(), Some(&serde_json::json!(true)));
+  assert!(*tree.pick_cursor::<bool>("flag").expect("read bool"));
+}
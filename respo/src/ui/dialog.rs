@@ -11,15 +11,16 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{Element, HtmlElement, KeyboardEvent, KeyboardEventInit, Node};
+use web_sys::{Element, HtmlElement, KeyboardEvent, KeyboardEventInit, Node, Window};
 
-use crate::component::effect::RespoEffect;
+use crate::component::effect::{RespoEffect, RespoEffectNode};
 use crate::css::CssColor;
 use crate::node::css::{
   CssColor::{Hsl, Hsla},
   CssDisplay, CssOverflow, CssPosition,
 };
 use crate::node::{DispatchFn, RespoEvent, RespoNode};
+use crate::RespoError;
 use crate::{app, input, static_styles, util, RespoComponent};
 
 pub(crate) const BUTTON_NAME: &str = "dialog-button";
@@ -38,9 +39,9 @@ pub(crate) struct EffectFocus {
 }
 
 impl RespoEffect for EffectFocus {
-  fn updated(&self, el: &Node) -> Result<(), String> {
+  fn updated(&self, el: RespoEffectNode) -> Result<(), RespoError> {
     let show: bool = self.show;
-    if show {
+    if let (true, Some(el)) = (show, el.web_node()) {
       focus_element(el, BUTTON_NAME)?;
     }
 
@@ -48,11 +49,14 @@ impl RespoEffect for EffectFocus {
   }
 }
 
-fn focus_element(el: &Node, name: &str) -> Result<(), String> {
-  match el.dyn_ref::<Element>().unwrap().query_selector(&format!(".{}", name)) {
+fn focus_element(el: &Node, name: &str) -> Result<(), RespoError> {
+  let el = el
+    .dyn_ref::<Element>()
+    .ok_or_else(|| RespoError::Plugin(format!("focus_element: expected an element to look for {}", name)))?;
+  match el.query_selector(&format!(".{}", name)) {
     Ok(Some(element)) => {
       match element.dyn_ref::<HtmlElement>() {
-        Some(el) => el.focus().unwrap(),
+        Some(el) => el.focus()?,
         None => {
           app::util::log!("focus_element: {} is not an HTML element", name);
         }
@@ -68,78 +72,94 @@ fn focus_element(el: &Node, name: &str) -> Result<(), String> {
   Ok(())
 }
 
+pub(crate) fn get_window() -> Result<Window, RespoError> {
+  web_sys::window().ok_or_else(|| RespoError::Plugin(String::from("window not found")))
+}
+
+/// first child of `el`, which is expected to be an HTML element
+fn first_html_child(el: &Node) -> Result<HtmlElement, RespoError> {
+  el.first_child()
+    .and_then(|child| child.dyn_into::<HtmlElement>().ok())
+    .ok_or_else(|| RespoError::Plugin(String::from("expected an HTML element as first child")))
+}
+
+/// clones the content into `<body/>` and animates it with `card_transform` before removing,
+/// so it fades out after the dialog itself is removed from the tree
+fn fade_out_cloned(el: &Node, card_transform: &'static str) -> Result<(), RespoError> {
+  let Some(target) = el.first_child() else {
+    util::warn_log!("content not found");
+    return Ok(());
+  };
+  let cloned = target
+    .clone_node_with_deep(true)?
+    .dyn_into::<HtmlElement>()
+    .map_err(|_| RespoError::Plugin(String::from("expected an HTML element to clone")))?;
+  let cloned = Rc::new(cloned); // outlive
+  let body = el
+    .owner_document()
+    .and_then(|document| document.body())
+    .ok_or_else(|| RespoError::Plugin(String::from("document body not found")))?;
+  body.append_child(&cloned)?;
+  // setTimeout
+  let window = get_window()?;
+  let immediate_call: Closure<dyn FnMut()> = Closure::once({
+    let cloned = cloned.to_owned();
+    move || {
+      let _ = cloned.style().set_property("opacity", "0");
+      if let Ok(card) = first_html_child(&cloned) {
+        let card_style = card.style();
+        let _ = card_style.set_property("transition-duration", "240ms");
+        let _ = card_style.set_property("transform", card_transform);
+      }
+    }
+  });
+  window.set_timeout_with_callback_and_timeout_and_arguments_0(immediate_call.as_ref().unchecked_ref(), 10)?;
+  immediate_call.forget();
+  let delay_call: Closure<dyn FnMut()> = Closure::once(move || {
+    cloned.remove();
+  });
+  window.set_timeout_with_callback_and_timeout_and_arguments_0(delay_call.as_ref().unchecked_ref(), 250)?;
+  delay_call.forget();
+  Ok(())
+}
+
+/// starts content from `card_transform` and transparent, then animates it into place
+fn fade_in(el: &Node, card_transform: &str, card_target: &'static str) -> Result<(), RespoError> {
+  let target = first_html_child(el)?;
+  let style = target.style();
+  let card_style = first_html_child(&target)?.style();
+  style.set_property("opacity", "0")?;
+  card_style.set_property("transform", card_transform)?;
+  let call = Closure::once(move || {
+    let _ = style.set_property("transition-duration", "240ms");
+    let _ = card_style.set_property("transition-duration", "240ms");
+    let _ = style.set_property("opacity", "1");
+    let _ = card_style.set_property("transform", card_target);
+  });
+  get_window()?.set_timeout_with_callback_and_timeout_and_arguments_0(call.as_ref().unchecked_ref(), 10)?;
+  call.forget();
+  Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct EffectModalFade {
   show: bool,
 }
 
 impl RespoEffect for EffectModalFade {
-  fn before_update(&self, el: &Node) -> Result<(), String> {
-    let show: bool = self.show;
-
-    if !show {
+  fn before_update(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    if let (false, Some(el)) = (self.show, el.web_node()) {
       // when closing, fade out the cloned element
-      match el.first_child() {
-        Some(target) => {
-          let d = target.clone_node_with_deep(true).unwrap();
-          let cloned = Rc::new(d.dyn_ref::<HtmlElement>().unwrap().to_owned()); // outlive
-          let document = el.owner_document().unwrap();
-          document.body().unwrap().append_child(&cloned).unwrap();
-          // setTimeout
-          let window = web_sys::window().unwrap();
-          let immediate_call: Closure<dyn FnMut()> = Closure::once({
-            let cloned = cloned.to_owned();
-            move || {
-              let style = cloned.style();
-              style.set_property("opacity", "0").unwrap();
-              let card = cloned.first_child().unwrap();
-              let card_style = card.dyn_ref::<HtmlElement>().unwrap().style();
-              card_style.set_property("transition-duration", "240ms").unwrap();
-              card_style.set_property("transform", "scale(0.94) translate(0px,-20px)").unwrap();
-            }
-          });
-          window
-            .set_timeout_with_callback_and_timeout_and_arguments_0(immediate_call.as_ref().unchecked_ref(), 10)
-            .unwrap();
-          immediate_call.forget();
-          let delay_call: Closure<dyn FnMut()> = Closure::once(move || {
-            cloned.remove();
-          });
-          window
-            .set_timeout_with_callback_and_timeout_and_arguments_0(delay_call.as_ref().unchecked_ref(), 250)
-            .unwrap();
-          delay_call.forget();
-        }
-        None => {
-          util::warn_log!("content not found");
-        }
-      }
+      fade_out_cloned(el, "scale(0.94) translate(0px,-20px)")?;
     }
     Ok(())
   }
 
-  fn updated(&self, el: &Node) -> Result<(), String> {
-    let show: bool = self.show;
-    if show {
+  fn updated(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    if let (true, Some(el)) = (self.show, el.web_node()) {
       // when opening, fade in the cloned element
-      let target = el.first_child().unwrap();
-      let style = target.dyn_ref::<HtmlElement>().unwrap().style();
-      let card_style = target.first_child().unwrap().dyn_ref::<HtmlElement>().unwrap().style();
-      style.set_property("opacity", "0").unwrap();
-      card_style.set_property("transform", "scale(0.94) translate(0px,-12px)").unwrap();
-      let call = Closure::once(move || {
-        style.set_property("transition-duration", "240ms").unwrap();
-        card_style.set_property("transition-duration", "240ms").unwrap();
-        style.set_property("opacity", "1").unwrap();
-        card_style.set_property("transform", "scale(1) translate(0px,0px)").unwrap();
-      });
-      let window = web_sys::window().unwrap();
-      window
-        .set_timeout_with_callback_and_timeout_and_arguments_0(call.as_ref().unchecked_ref(), 10)
-        .unwrap();
-      call.forget();
+      fade_in(el, "scale(0.94) translate(0px,-12px)", "scale(1) translate(0px,0px)")?;
     }
-
     Ok(())
   }
 }
@@ -150,74 +170,19 @@ pub(crate) struct EffectDrawerFade {
 }
 
 impl RespoEffect for EffectDrawerFade {
-  fn before_update(&self, el: &Node) -> Result<(), String> {
-    let show = self.show;
-
-    if !show {
+  fn before_update(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    if let (false, Some(el)) = (self.show, el.web_node()) {
       // when closing, fade out the cloned element
-      match el.first_child() {
-        Some(target) => {
-          let d = target.clone_node_with_deep(true).unwrap();
-          let cloned = Rc::new(d.dyn_ref::<HtmlElement>().unwrap().to_owned()); // outlive
-          let document = el.owner_document().unwrap();
-          document.body().unwrap().append_child(&cloned).unwrap();
-          // setTimeout
-          let window = web_sys::window().unwrap();
-          let immediate_call: Closure<dyn FnMut()> = Closure::once({
-            let cloned = cloned.to_owned();
-            move || {
-              let style = cloned.style();
-              style.set_property("opacity", "0").unwrap();
-              let card = cloned.first_child().unwrap();
-              let card_style = card.dyn_ref::<HtmlElement>().unwrap().style();
-              card_style.set_property("transition-duration", "240ms").unwrap();
-              card_style.set_property("transform", "translate(100%,0px)").unwrap();
-            }
-          });
-          window
-            .set_timeout_with_callback_and_timeout_and_arguments_0(immediate_call.as_ref().unchecked_ref(), 10)
-            .unwrap();
-          immediate_call.forget();
-          let delay_call: Closure<dyn FnMut()> = Closure::once(move || {
-            cloned.remove();
-          });
-          window
-            .set_timeout_with_callback_and_timeout_and_arguments_0(delay_call.as_ref().unchecked_ref(), 250)
-            .unwrap();
-          delay_call.forget();
-        }
-        None => {
-          app::util::warn_log!("content not found");
-        }
-      }
+      fade_out_cloned(el, "translate(100%,0px)")?;
     }
-
     Ok(())
   }
 
-  fn updated(&self, el: &Node) -> Result<(), String> {
-    let show = self.show;
-
-    if show {
+  fn updated(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    if let (true, Some(el)) = (self.show, el.web_node()) {
       // when opening, fade in the cloned element
-      let target = el.first_child().unwrap();
-      let style = target.dyn_ref::<HtmlElement>().unwrap().style();
-      let card_style = target.first_child().unwrap().dyn_ref::<HtmlElement>().unwrap().style();
-      style.set_property("opacity", "0").unwrap();
-      card_style.set_property("transform", "translate(100%, 0px)").unwrap();
-      let call = Closure::once(move || {
-        style.set_property("transition-duration", "240ms").unwrap();
-        card_style.set_property("transition-duration", "240ms").unwrap();
-        style.set_property("opacity", "1").unwrap();
-        card_style.set_property("transform", "translate(0px,0px)").unwrap();
-      });
-      let window = web_sys::window().unwrap();
-      window
-        .set_timeout_with_callback_and_timeout_and_arguments_0(call.as_ref().unchecked_ref(), 10)
-        .unwrap();
-      call.forget();
+      fade_in(el, "translate(100%, 0px)", "translate(0px,0px)")?;
     }
-
     Ok(())
   }
 }
@@ -226,9 +191,10 @@ impl RespoEffect for EffectDrawerFade {
 struct EffectModalClose {}
 
 impl RespoEffect for EffectModalClose {
-  fn mounted(&self, el: &Node) -> Result<(), String> {
+  fn mounted(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    let Some(el) = el.web_node() else { return Ok(()) };
     let el = Rc::new(el.to_owned());
-    let window = web_sys::window().unwrap();
+    let window = get_window()?;
     let listener = Closure::wrap(Box::new({
       let el = el.to_owned();
       move |event: web_sys::KeyboardEvent| {
@@ -239,27 +205,29 @@ impl RespoEffect for EffectModalClose {
         init_dict.set_view(event.view().as_ref());
         init_dict.set_location(event.location());
         init_dict.set_key_code(event.key_code());
-        let new_event = KeyboardEvent::new_with_keyboard_event_init_dict(&event.type_(), &init_dict)
-          .expect("Failed to create new KeyboardEvent from init dict");
-
-        el.dispatch_event(&new_event).unwrap();
+        match KeyboardEvent::new_with_keyboard_event_init_dict(&event.type_(), &init_dict) {
+          Ok(new_event) => {
+            if let Err(e) = el.dispatch_event(&new_event) {
+              util::warn_log!("failed to dispatch keyboard event: {:?}", e);
+            }
+          }
+          Err(e) => util::warn_log!("failed to create keyboard event: {:?}", e),
+        }
       }
     }) as Box<dyn FnMut(_)>);
-    window
-      .add_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref())
-      .unwrap();
+    window.add_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref())?;
     let _ = Reflect::set(&el, &JsValue::from_str(TEMP_LISTENER), listener.as_ref().unchecked_ref());
     listener.forget();
     Ok(())
   }
 
-  fn before_unmount(&self, el: &Node) -> Result<(), String> {
+  fn before_unmount(&self, el: RespoEffectNode) -> Result<(), RespoError> {
+    let Some(el) = el.web_node() else { return Ok(()) };
     let el = Rc::new(el.to_owned());
-    let listener = Reflect::get(&el, &JsValue::from_str(TEMP_LISTENER)).unwrap();
-    let window = web_sys::window().unwrap();
-    window
-      .remove_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref())
-      .unwrap();
+    let listener = Reflect::get(&el, &JsValue::from_str(TEMP_LISTENER))?;
+    if listener.is_function() {
+      get_window()?.remove_event_listener_with_callback("keydown", listener.unchecked_ref())?;
+    }
     let _ = Reflect::set(&el, &JsValue::from_str(TEMP_LISTENER), &JsValue::NULL);
 
     Ok(())
@@ -270,9 +238,9 @@ impl RespoEffect for EffectModalClose {
 const TEMP_LISTENER: &str = "temp_listener";
 
 /// handle global keydown event
-fn comp_esc_listener<T, U>(_show: bool, on_close: Rc<U>) -> Result<RespoNode<T>, String>
+fn comp_esc_listener<T, U>(_show: bool, on_close: Rc<U>) -> Result<RespoNode<T>, RespoError>
 where
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   T: Clone + Debug,
 {
   Ok(
@@ -280,7 +248,7 @@ where
       "esc-listener",
       input()
         .style(respo_style().display(CssDisplay::None))
-        .on_keydown(move |e, dispatch| -> Result<(), String> {
+        .on_keydown(move |e, dispatch| -> Result<(), RespoError> {
           if let RespoEvent::Keyboard { key, .. } = e {
             if key == "Escape" {
               on_close(dispatch)?;
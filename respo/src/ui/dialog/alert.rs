@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 use crate::ui::dialog::{css_backdrop, css_button, css_modal_card};
 use crate::ui::dialog::{EffectFocus, EffectModalFade, BUTTON_NAME};
 use crate::ui::{column, respo_style, ui_button, ui_center, ui_fullscreen, ui_global, ui_row_parted};
+use crate::RespoError;
 
 use crate::node::css::{CssLineHeight, CssPosition, RespoStyle};
 use crate::node::{DispatchFn, RespoAction, RespoEvent, RespoNode};
@@ -31,10 +32,10 @@ pub struct AlertOptions {
   pub button_text: Option<String>,
 }
 
-fn comp_alert_modal<T, U, V>(options: AlertOptions, show: bool, on_read: U, on_close: V) -> Result<RespoNode<T>, String>
+fn comp_alert_modal<T, U, V>(options: AlertOptions, show: bool, on_read: U, on_close: V) -> Result<RespoNode<T>, RespoError>
 where
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
-  V: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
+  V: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   T: Clone + Debug,
 {
   let read = Rc::new(on_read);
@@ -49,7 +50,7 @@ where
           .style(options.backdrop_style)
           .on_click({
             let close = close.to_owned();
-            move |e, dispatch| -> Result<(), String> {
+            move |e, dispatch| -> Result<(), RespoError> {
               if let RespoEvent::Click { original_event, .. } = e {
                 // stop propagation to prevent closing the modal
                 original_event.stop_propagation();
@@ -63,7 +64,7 @@ where
               .class_list(&[column(), ui_global(), css_modal_card()])
               .style(respo_style().line_height(CssLineHeight::Px(32.0)))
               .style(options.card_style)
-              .on_click(move |e, _dispatch| -> Result<(), String> {
+              .on_click(move |e, _dispatch| -> Result<(), RespoError> {
                 // nothing to do
                 if let RespoEvent::Click { original_event, .. } = e {
                   // stop propagation to prevent closing the modal
@@ -81,7 +82,7 @@ where
                     .inner_text(options.button_text.unwrap_or_else(|| "Read".to_owned()))
                     .on_click({
                       let close = close.to_owned();
-                      move |_e, dispatch| -> Result<(), String> {
+                      move |_e, dispatch| -> Result<(), RespoError> {
                         read(dispatch.to_owned())?;
                         close(dispatch)?;
                         Ok(())
@@ -107,19 +108,19 @@ where
 pub trait AlertPluginInterface<T, U>
 where
   T: Debug + Clone + RespoAction,
-  U: Fn(DispatchFn<T>) -> Result<(), String>,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError>,
 {
   /// renders virtual dom for alert modal
-  fn render(&self) -> Result<RespoNode<T>, String>
+  fn render(&self) -> Result<RespoNode<T>, RespoError>
   where
     T: Clone + Debug;
   /// to show alert, second parameter is a message that could overwrite the default message
-  fn show(&self, dispatch: DispatchFn<T>, text: Option<String>) -> Result<(), String>;
+  fn show(&self, dispatch: DispatchFn<T>, text: Option<String>) -> Result<(), RespoError>;
   /// to close alert
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String>;
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError>;
 
   /// show alert with options, `on_read` is the callback function when read button is clicked
-  fn new(states: RespoStatesTree, options: AlertOptions, on_read: U) -> Result<Self, String>
+  fn new(states: RespoStatesTree, options: AlertOptions, on_read: U) -> Result<Self, RespoError>
   where
     Self: std::marker::Sized;
 
@@ -139,7 +140,7 @@ struct AlertPluginState {
 pub struct AlertPlugin<T, U>
 where
   T: Clone + Debug,
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
 {
   state: Rc<AlertPluginState>,
   options: AlertOptions,
@@ -153,9 +154,9 @@ where
 impl<T, U> AlertPluginInterface<T, U> for AlertPlugin<T, U>
 where
   T: Clone + Debug + RespoAction,
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static + Copy,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static + Copy,
 {
-  fn render(&self) -> Result<RespoNode<T>, String> {
+  fn render(&self) -> Result<RespoNode<T>, RespoError> {
     let on_read = self.on_read;
     let cursor = &self.cursor;
     let state = &self.state;
@@ -196,12 +197,12 @@ where
       },
     )
   }
-  fn show(&self, dispatch: DispatchFn<T>, text: Option<String>) -> Result<(), String> {
+  fn show(&self, dispatch: DispatchFn<T>, text: Option<String>) -> Result<(), RespoError> {
     let s = AlertPluginState { show: true, text };
     dispatch.run_state(&self.cursor, s)?;
     Ok(())
   }
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String> {
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
     let s = AlertPluginState {
       show: false,
       text: self.text.to_owned(),
@@ -210,7 +211,7 @@ where
     Ok(())
   }
 
-  fn new(states: RespoStatesTree, options: AlertOptions, on_read: U) -> Result<Self, String> {
+  fn new(states: RespoStatesTree, options: AlertOptions, on_read: U) -> Result<Self, RespoError> {
     let cursor = states.path();
     let state = states.cast_branch::<AlertPluginState>();
 
@@ -11,6 +11,7 @@ use wasm_bindgen::{JsCast, JsValue};
 
 use crate::ui::dialog::{css_backdrop, css_button, css_modal_card};
 use crate::ui::{column, respo_style, ui_button, ui_center, ui_fullscreen, ui_global, ui_row_parted};
+use crate::RespoError;
 
 use crate::node::css::{CssLineHeight, CssPosition, RespoStyle};
 use crate::node::{DispatchFn, RespoAction, RespoEvent, RespoNode};
@@ -20,7 +21,7 @@ use crate::states_tree::{RespoState, RespoStatesTree};
 
 use crate::ui::dialog::{EffectFocus, EffectModalFade, BUTTON_NAME};
 
-use super::comp_esc_listener;
+use super::{comp_esc_listener, get_window};
 
 const NEXT_TASK_NAME: &str = "_RESPO_CONFIRM_NEXT_TASK";
 
@@ -37,10 +38,10 @@ pub struct ConfirmOptions {
   button_text: Option<String>,
 }
 
-fn comp_confirm_modal<T, U, V>(options: ConfirmOptions, show: bool, on_confirm: U, on_close: V) -> Result<RespoNode<T>, String>
+fn comp_confirm_modal<T, U, V>(options: ConfirmOptions, show: bool, on_confirm: U, on_close: V) -> Result<RespoNode<T>, RespoError>
 where
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
-  V: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
+  V: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   T: Clone + Debug,
 {
   let confirm = Rc::new(on_confirm);
@@ -55,7 +56,7 @@ where
           .style(options.backdrop_style)
           .on_click({
             let close = close.to_owned();
-            move |e, dispatch| -> Result<(), String> {
+            move |e, dispatch| -> Result<(), RespoError> {
               if let RespoEvent::Click { original_event, .. } = e {
                 // stop propagation to prevent closing the modal
                 original_event.stop_propagation();
@@ -69,7 +70,7 @@ where
               .class_list(&[column(), ui_global(), css_modal_card()])
               .style(respo_style().line_height(CssLineHeight::Px(32.0)))
               .style(options.card_style)
-              .on_click(move |e, _dispatch| -> Result<(), String> {
+              .on_click(move |e, _dispatch| -> Result<(), RespoError> {
                 // nothing to do
                 if let RespoEvent::Click { original_event, .. } = e {
                   // stop propagation to prevent closing the modal
@@ -87,7 +88,7 @@ where
                     .inner_text(options.button_text.unwrap_or_else(|| "Confirm".to_owned()))
                     .on_click({
                       let close = close.to_owned();
-                      move |_e, dispatch| -> Result<(), String> {
+                      move |_e, dispatch| -> Result<(), RespoError> {
                         confirm(dispatch.to_owned())?;
                         close(dispatch)?;
                         Ok(())
@@ -113,22 +114,22 @@ where
 pub trait ConfirmPluginInterface<T, U>
 where
   T: Debug + Clone + RespoAction,
-  U: Fn(DispatchFn<T>) -> Result<(), String>,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError>,
 {
   /// renders UI
-  fn render(&self) -> Result<RespoNode<T>, String>
+  fn render(&self) -> Result<RespoNode<T>, RespoError>
   where
     T: Clone + Debug;
   /// to show dialog, second parameter is a callback when confirmed,
   /// the callback is implemented dirty, it perform directly after confirmed
-  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), String>
+  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), RespoError>
   where
-    V: Fn() -> Result<(), String> + 'static;
+    V: Fn() -> Result<(), RespoError> + 'static;
   /// to close dialog
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String>;
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError>;
 
   /// creates a new instance of confirm plugin, second parameter is a callback when confirmed
-  fn new(states: RespoStatesTree, options: ConfirmOptions, on_confirm: U) -> Result<Self, String>
+  fn new(states: RespoStatesTree, options: ConfirmOptions, on_confirm: U) -> Result<Self, RespoError>
   where
     Self: std::marker::Sized;
 
@@ -146,7 +147,7 @@ struct ConfirmPluginState {
 pub struct ConfirmPlugin<T, U>
 where
   T: Clone + Debug,
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
 {
   state: Rc<ConfirmPluginState>,
   options: ConfirmOptions,
@@ -160,9 +161,9 @@ where
 impl<T, U> ConfirmPluginInterface<T, U> for ConfirmPlugin<T, U>
 where
   T: Clone + Debug + RespoAction,
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static + Copy,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static + Copy,
 {
-  fn render(&self) -> Result<RespoNode<T>, String> {
+  fn render(&self) -> Result<RespoNode<T>, RespoError> {
     let on_confirm = self.on_confirm;
     let cursor = self.cursor.to_owned();
     let state = self.state.to_owned();
@@ -175,15 +176,14 @@ where
         let st = state.to_owned();
         move |dispatch| {
           on_confirm(dispatch.to_owned())?;
-          let window = web_sys::window().expect("window");
+          let window = get_window()?;
           // TODO dirty global variable
           let task = Reflect::get(&window, &JsValue::from_str(NEXT_TASK_NAME));
           if let Ok(f) = task {
-            if f.is_function() {
-              let f = f.dyn_into::<Function>().unwrap();
+            if let Ok(f) = f.dyn_into::<Function>() {
               let _ = f.apply(&JsValue::NULL, &Array::new());
             } else {
-              return Err("_NEXT_TASK is not a function".to_owned());
+              return Err(RespoError::Plugin("_NEXT_TASK is not a function".to_owned()));
             }
           } else {
             app::util::log!("next task is None");
@@ -194,7 +194,7 @@ where
           };
           dispatch.run_state(&c, s)?;
           // clean up leaked closure
-          let window = web_sys::window().expect("window");
+          let window = get_window()?;
           let _ = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), &JsValue::NULL);
           Ok(())
         }
@@ -209,23 +209,23 @@ where
           };
           dispatch.run_state(&c, s)?;
           // clean up leaked closure
-          let window = web_sys::window().expect("window");
+          let window = get_window()?;
           let _ = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), &JsValue::NULL);
           Ok(())
         }
       },
     )
   }
-  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), String>
+  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), RespoError>
   where
-    V: Fn() -> Result<(), String> + 'static,
+    V: Fn() -> Result<(), RespoError> + 'static,
   {
     let s = ConfirmPluginState {
       show: true,
       text: self.state.text.to_owned(),
     };
     let task = Closure::once(next_task);
-    let window = web_sys::window().unwrap();
+    let window = get_window()?;
     // dirty global variable to store a shared callback
     if let Err(e) = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), task.as_ref()) {
       app::util::error_log!("failed to store next task {:?}", e);
@@ -234,7 +234,7 @@ where
     dispatch.run_state(&self.cursor, s)?;
     Ok(())
   }
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String> {
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
     let s = ConfirmPluginState {
       show: false,
       text: self.text.to_owned(),
@@ -243,7 +243,7 @@ where
     Ok(())
   }
 
-  fn new(states: RespoStatesTree, options: ConfirmOptions, on_confirm: U) -> Result<Self, String> {
+  fn new(states: RespoStatesTree, options: ConfirmOptions, on_confirm: U) -> Result<Self, RespoError> {
     let cursor = states.path();
     let state = states.cast_branch::<ConfirmPluginState>();
 
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::ui::dialog::{css_backdrop, css_modal_card};
 use crate::ui::{column, respo_style, ui_center, ui_fullscreen, ui_global};
+use crate::RespoError;
 
 use crate::node::css::{CssLineHeight, CssPosition, RespoStyle};
 use crate::node::{DispatchFn, RespoAction, RespoEvent, RespoNode};
@@ -35,7 +36,7 @@ where
   pub render: ModalRenderer<T>,
 }
 
-type ModalRendererFn<T> = dyn Fn(Rc<dyn Fn(DispatchFn<T>) -> Result<(), String>>) -> Result<RespoNode<T>, String>;
+type ModalRendererFn<T> = dyn Fn(Rc<dyn Fn(DispatchFn<T>) -> Result<(), RespoError>>) -> Result<RespoNode<T>, RespoError>;
 
 /// wraps render function
 #[derive(Clone)]
@@ -67,22 +68,22 @@ where
 {
   pub fn new<V>(renderer: V) -> Self
   where
-    V: Fn(Rc<dyn Fn(DispatchFn<T>) -> Result<(), String>>) -> Result<RespoNode<T>, String> + 'static,
+    V: Fn(Rc<dyn Fn(DispatchFn<T>) -> Result<(), RespoError>>) -> Result<RespoNode<T>, RespoError> + 'static,
   {
     Self(Rc::new(renderer))
   }
 
-  pub fn run<V>(&self, close: V) -> Result<RespoNode<T>, String>
+  pub fn run<V>(&self, close: V) -> Result<RespoNode<T>, RespoError>
   where
-    V: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+    V: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   {
     (self.0)(Rc::new(close))
   }
 }
 
-fn comp_modal<T, U>(options: ModalOptions<T>, show: bool, on_close: U) -> Result<RespoNode<T>, String>
+fn comp_modal<T, U>(options: ModalOptions<T>, show: bool, on_close: U) -> Result<RespoNode<T>, RespoError>
 where
-  U: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   T: Clone + Debug,
 {
   let close = Rc::new(on_close);
@@ -97,7 +98,7 @@ where
           .to_owned()
           .on_click({
             let close = close.to_owned();
-            move |e, dispatch| -> Result<(), String> {
+            move |e, dispatch| -> Result<(), RespoError> {
               if let RespoEvent::Click { original_event, .. } = e {
                 // stop propagation to prevent closing the modal
                 original_event.stop_propagation();
@@ -112,7 +113,7 @@ where
               .style(respo_style().padding(0).line_height(CssLineHeight::Px(32.0)))
               .style(options.card_style)
               .to_owned()
-              .on_click(move |e, _dispatch| -> Result<(), String> {
+              .on_click(move |e, _dispatch| -> Result<(), RespoError> {
                 // nothing to do
                 if let RespoEvent::Click { original_event, .. } = e {
                   // stop propagation to prevent closing the modal
@@ -128,7 +129,7 @@ where
                 space(None, Some(8)).to_node(),
                 {
                   let close = close.to_owned();
-                  options.render.run(move |dispatch| -> Result<(), String> {
+                  options.render.run(move |dispatch| -> Result<(), RespoError> {
                     close(dispatch)?;
                     Ok(())
                   })?
@@ -154,15 +155,15 @@ where
   T: Debug + Clone + RespoAction,
 {
   /// renders UI
-  fn render(&self) -> Result<RespoNode<T>, String>
+  fn render(&self) -> Result<RespoNode<T>, RespoError>
   where
     T: Clone + Debug;
   /// to show modal
-  fn show(&self, dispatch: DispatchFn<T>) -> Result<(), String>;
+  fn show(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError>;
   /// to close modal
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String>;
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError>;
 
-  fn new(states: RespoStatesTree, options: ModalOptions<T>) -> Result<Self, String>
+  fn new(states: RespoStatesTree, options: ModalOptions<T>) -> Result<Self, RespoError>
   where
     Self: std::marker::Sized;
 
@@ -192,7 +193,7 @@ impl<T> ModalPluginInterface<T> for ModalPlugin<T>
 where
   T: Clone + Debug + RespoAction,
 {
-  fn render(&self) -> Result<RespoNode<T>, String> {
+  fn render(&self) -> Result<RespoNode<T>, RespoError> {
     let cursor = self.cursor.to_owned();
 
     comp_modal(self.options.to_owned(), self.state.show, move |dispatch: DispatchFn<_>| {
@@ -201,18 +202,18 @@ where
       Ok(())
     })
   }
-  fn show(&self, dispatch: DispatchFn<T>) -> Result<(), String> {
+  fn show(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
     let s = ModalPluginState { show: true };
     dispatch.run_state(&self.cursor, s)?;
     Ok(())
   }
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String> {
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
     let s = ModalPluginState { show: false };
     dispatch.run_state(&self.cursor, s)?;
     Ok(())
   }
 
-  fn new(states: RespoStatesTree, options: ModalOptions<T>) -> Result<Self, String> {
+  fn new(states: RespoStatesTree, options: ModalOptions<T>) -> Result<Self, RespoError> {
     let cursor = states.path();
     let state = states.cast_branch::<ModalPluginState>();
 
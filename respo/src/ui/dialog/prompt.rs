@@ -12,6 +12,7 @@ use wasm_bindgen::{JsCast, JsValue};
 
 use crate::ui::dialog::{css_backdrop, css_button, css_modal_card, EffectModalFade, BUTTON_NAME};
 use crate::ui::{column, respo_style, ui_button, ui_center, ui_fullscreen, ui_global, ui_input, ui_row_parted, ui_textarea};
+use crate::RespoError;
 
 use crate::node::css::{CssColor, CssLineHeight, CssPosition, RespoStyle};
 use crate::node::{DispatchFn, RespoAction, RespoEvent, RespoNode};
@@ -19,7 +20,7 @@ use crate::{app, button, div, input, space, span, static_styles, textarea, util,
 
 use crate::states_tree::{RespoState, RespoStatesTree};
 
-use super::comp_esc_listener;
+use super::{comp_esc_listener, get_window};
 
 const NEXT_TASK_NAME: &str = "_RESPO_PROMPT_NEXT_TASK";
 
@@ -79,10 +80,10 @@ fn comp_prompt_modal<T, U, V>(
   show: bool,
   on_submit: U,
   on_close: V,
-) -> Result<RespoNode<T>, String>
+) -> Result<RespoNode<T>, RespoError>
 where
-  U: Fn(String, DispatchFn<T>) -> Result<(), String> + 'static,
-  V: Fn(DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(String, DispatchFn<T>) -> Result<(), RespoError> + 'static,
+  V: Fn(DispatchFn<T>) -> Result<(), RespoError> + 'static,
   T: Clone + Debug + RespoAction,
 {
   let cursor = states.path();
@@ -101,7 +102,7 @@ where
 
   let on_text_input = {
     let cursor = cursor.to_owned();
-    move |e, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |e, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       if let RespoEvent::Input { value, .. } = e {
         dispatch.run_state(&cursor, InputState { draft: value, error: None })?;
       }
@@ -112,7 +113,7 @@ where
   let check_submit = {
     let close = close.to_owned();
     let cursor = cursor.to_owned();
-    move |text: &str, dispatch: DispatchFn<_>| -> Result<(), String> {
+    move |text: &str, dispatch: DispatchFn<_>| -> Result<(), RespoError> {
       app::util::log!("validator: {:?}", &options.validator);
       if let Some(validator) = &options.validator {
         // let validator = validator.borrow();
@@ -159,7 +160,7 @@ where
           .to_owned()
           .on_click({
             let close = close.to_owned();
-            move |e, dispatch| -> Result<(), String> {
+            move |e, dispatch| -> Result<(), RespoError> {
               if let RespoEvent::Click { original_event, .. } = e {
                 // stop propagation to prevent closing the modal
                 original_event.stop_propagation();
@@ -178,7 +179,7 @@ where
               .style(respo_style().line_height(CssLineHeight::Px(32.0)))
               .style(options.card_style)
               .style(options.input_style)
-              .on_click(move |e, _dispatch| -> Result<(), String> {
+              .on_click(move |e, _dispatch| -> Result<(), RespoError> {
                 // nothing to do
                 if let RespoEvent::Click { original_event, .. } = e {
                   // stop propagation to prevent closing the modal
@@ -205,7 +206,7 @@ where
                   button()
                     .class_list(&[ui_button(), css_button(), BUTTON_NAME.to_owned()])
                     .inner_text(options.button_text.unwrap_or_else(|| "Submit".to_owned()))
-                    .on_click(move |_e, dispatch| -> Result<(), String> {
+                    .on_click(move |_e, dispatch| -> Result<(), RespoError> {
                       check_submit(&state.draft, dispatch)?;
                       Ok(())
                     }),
@@ -229,22 +230,22 @@ where
 pub trait PromptPluginInterface<T, U>
 where
   T: Debug + Clone + RespoAction,
-  U: Fn(String, DispatchFn<T>) -> Result<(), String>,
+  U: Fn(String, DispatchFn<T>) -> Result<(), RespoError>,
 {
   /// renders UI
-  fn render(&self) -> Result<RespoNode<T>, String>
+  fn render(&self) -> Result<RespoNode<T>, RespoError>
   where
     T: Clone + Debug;
   /// to show prompt dialog, second parameter is the callback task when the dialog is read,
   /// the callback is stored in a dirty to provide syntax sugar
-  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), String>
+  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), RespoError>
   where
-    V: Fn(String) -> Result<(), String> + 'static;
+    V: Fn(String) -> Result<(), RespoError> + 'static;
   /// to close prompt dialog
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String>;
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError>;
 
   /// initialize the plugin, second parameter is the callback task when submitted,
-  fn new(states: RespoStatesTree, options: PromptOptions, on_submit: U) -> Result<Self, String>
+  fn new(states: RespoStatesTree, options: PromptOptions, on_submit: U) -> Result<Self, RespoError>
   where
     Self: std::marker::Sized;
 
@@ -263,7 +264,7 @@ struct PromptPluginState {
 pub struct PromptPlugin<T, U>
 where
   T: Clone + Debug,
-  U: Fn(String, DispatchFn<T>) -> Result<(), String> + 'static,
+  U: Fn(String, DispatchFn<T>) -> Result<(), RespoError> + 'static,
 {
   states: RespoStatesTree,
   state: Rc<PromptPluginState>,
@@ -278,9 +279,9 @@ where
 impl<T, U> PromptPluginInterface<T, U> for PromptPlugin<T, U>
 where
   T: Clone + Debug + RespoAction,
-  U: Fn(String, DispatchFn<T>) -> Result<(), String> + 'static + Copy,
+  U: Fn(String, DispatchFn<T>) -> Result<(), RespoError> + 'static + Copy,
 {
-  fn render(&self) -> Result<RespoNode<T>, String> {
+  fn render(&self) -> Result<RespoNode<T>, RespoError> {
     let on_submit = self.on_submit;
     let cursor = self.cursor.to_owned();
     let state = self.state.to_owned();
@@ -294,17 +295,16 @@ where
         let state = state.to_owned();
         move |content, dispatch| {
           on_submit(content.to_owned(), dispatch.to_owned())?;
-          let window = web_sys::window().expect("window");
+          let window = get_window()?;
           // TODO dirty global variable
           let task = Reflect::get(&window, &JsValue::from_str(NEXT_TASK_NAME));
           if let Ok(f) = task {
-            if f.is_function() {
-              let f = f.dyn_into::<Function>().unwrap();
+            if let Ok(f) = f.dyn_into::<Function>() {
               let arr = Array::new();
               arr.push(&JsValue::from_str(&content));
               let _ = f.apply(&JsValue::NULL, &arr);
             } else {
-              return Err("_NEXT_TASK is not a function".to_owned());
+              return Err(RespoError::Plugin("_NEXT_TASK is not a function".to_owned()));
             }
           } else {
             app::util::log!("next task is None");
@@ -315,7 +315,7 @@ where
           };
           dispatch.run_state(&cursor, s)?;
           // clean up leaked closure
-          let window = web_sys::window().expect("window");
+          let window = get_window()?;
           let _ = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), &JsValue::NULL);
           Ok(())
         }
@@ -327,22 +327,22 @@ where
         };
         dispatch.run_state(&cursor, s)?;
         // clean up leaked closure
-        let window = web_sys::window().expect("window");
+        let window = get_window()?;
         let _ = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), &JsValue::NULL);
         Ok(())
       },
     )
   }
-  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), String>
+  fn show<V>(&self, dispatch: DispatchFn<T>, next_task: V) -> Result<(), RespoError>
   where
-    V: Fn(String) -> Result<(), String> + 'static,
+    V: Fn(String) -> Result<(), RespoError> + 'static,
   {
     let s = PromptPluginState {
       show: true,
       text: self.state.text.to_owned(),
     };
     let task = Closure::once(next_task);
-    let window = web_sys::window().unwrap();
+    let window = get_window()?;
     // dirty global variable to store a shared callback
     if let Err(e) = Reflect::set(&window, &JsValue::from_str(NEXT_TASK_NAME), task.as_ref()) {
       util::error_log!("failed to store next task {:?}", e);
@@ -351,7 +351,7 @@ where
     dispatch.run_state(&self.cursor, s)?;
     Ok(())
   }
-  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), String> {
+  fn close(&self, dispatch: DispatchFn<T>) -> Result<(), RespoError> {
     let s = PromptPluginState {
       show: false,
       text: self.text.to_owned(),
@@ -360,7 +360,7 @@ where
     Ok(())
   }
 
-  fn new(states: RespoStatesTree, options: PromptOptions, on_submit: U) -> Result<Self, String> {
+  fn new(states: RespoStatesTree, options: PromptOptions, on_submit: U) -> Result<Self, RespoError> {
     let cursor = states.path();
     let state = states.cast_branch::<PromptPluginState>();
 
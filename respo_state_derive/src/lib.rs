@@ -19,13 +19,13 @@ fn impl_respo_state_macro(ast: &syn::DeriveInput) -> TokenStream {
       fn backup(&self) -> Option<serde_json::Value> {
         serde_json::to_value(self).ok()
       }
-      fn restore_from(&mut self, s: &serde_json::Value) -> Result<(), String> {
+      fn restore_from(&mut self, s: &serde_json::Value) -> Result<(), respo::RespoError> {
         match serde_json::from_value(s.to_owned()) {
           Ok(v) => {
             *self = v;
             Ok(())
           }
-          Err(e) => Err(format!("failed to deserialize: {:?}", e)),
+          Err(e) => Err(respo::RespoError::StateRestore(format!("failed to deserialize: {:?}", e))),
         }
       }
     }